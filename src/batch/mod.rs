@@ -0,0 +1,28 @@
+//! Batch proving and verification of Groth16 proofs for the same parameters.
+//!
+//! [`prove_batch`] proves many preimages on a pool of threads sharing the
+//! parameters. [`BatchVerifier`] checks many proofs at once: each proof `i` is
+//! weighted by a random scalar `r_i` and the verification equations are summed
+//! up, so that a whole batch takes a single multi-Miller loop and a single
+//! final exponentiation:
+//!
+//! ```text
+//! prod e(r_i A_i, B_i) * e(sum r_i L_i, -gamma) * e(sum r_i C_i, -delta)
+//!     == e(alpha, beta)^(sum r_i)
+//! ```
+//!
+//! where `L_i` is the combination of the `IC` points with the public inputs
+//! of proof `i`. An invalid proof passes only if the weights happen to cancel
+//! it out, with negligible probability. When a batch fails, it is split in
+//! halves until every invalid proof is found and checked on its own with
+//! `groth16::verify_proof`.
+
+#[cfg(feature = "prover")]
+mod prove;
+#[cfg(feature = "verifier")]
+mod verify;
+
+#[cfg(feature = "prover")]
+pub use prove::prove_batch;
+#[cfg(feature = "verifier")]
+pub use verify::BatchVerifier;
@@ -0,0 +1,77 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use bellman::groth16::{self, Parameters, Proof};
+use bls12_381::Bls12;
+use rand_core::RngCore;
+
+use crate::{CircuitConfig, MyCircuit, Result};
+
+/// Proves knowledge of every preimage with `params`, generated for `config`,
+/// on `threads` threads. A preimage with a bad length for `config` gets an
+/// error instead of a proof.
+///
+/// The proof of preimage `i` draws its randomness from `rng(i)`, on the
+/// thread that computes it, so a deterministic `rng` gives the same proofs
+/// whatever the number of threads. Pass `|_| OsRng` outside of tests.
+///
+/// `on_proof` is called on the calling thread with the index of each
+/// preimage and its result as soon as it is done, so in no particular order.
+/// The returned results are in the order of the preimages. Each proof is
+/// itself computed on bellman's own worker pool, so a few threads are
+/// usually enough to keep every core busy.
+pub fn prove_batch<G, R, F>(
+    params: &Parameters<Bls12>,
+    config: CircuitConfig,
+    preimages: &[Vec<u8>],
+    threads: usize,
+    rng: G,
+    mut on_proof: F,
+) -> Vec<Result<Proof<Bls12>>>
+where
+    G: Fn(usize) -> R + Sync,
+    R: RngCore,
+    F: FnMut(usize, &Result<Proof<Bls12>>),
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut results = Vec::with_capacity(preimages.len());
+    results.resize_with(preimages.len(), || None);
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1).min(preimages.len()) {
+            let sender = sender.clone();
+            let next = &next;
+            let rng = &rng;
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= preimages.len() {
+                    break;
+                }
+                let result = config.check_preimage(&preimages[i]).and_then(|()| {
+                    let c = MyCircuit::with_preimage(config, preimages[i].clone());
+                    Ok(groth16::create_random_proof(c, params, &mut rng(i))?)
+                });
+                if sender.send((i, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        // Only the workers hold senders now, so the loop ends with them.
+        drop(sender);
+        for (i, result) in receiver {
+            on_proof(i, &result);
+            results[i] = Some(result);
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every preimage was proven"))
+        .collect()
+}
@@ -0,0 +1,147 @@
+use bellman::groth16::{self, PreparedVerifyingKey, Proof, VerifyingKey};
+use bls12_381::{multi_miller_loop, Bls12, G1Affine, G1Projective, G2Prepared, Gt, Scalar};
+use ff::Field;
+use rand_core::RngCore;
+
+use crate::{proof::ProofBundle, Error, Result};
+
+/// Verifier for many proofs made for the same verifying key.
+///
+/// bellman's `PreparedVerifyingKey` keeps its fields to itself, so this
+/// prepares its own copy of the key next to it. Like a prepared key, it is
+/// meant to be built once and reused for every batch.
+pub struct BatchVerifier {
+    alpha_g1_beta_g2: Gt,
+    neg_gamma_g2: G2Prepared,
+    neg_delta_g2: G2Prepared,
+    ic: Vec<G1Affine>,
+    pvk: PreparedVerifyingKey<Bls12>,
+}
+
+impl BatchVerifier {
+    pub fn new(vk: &VerifyingKey<Bls12>) -> Self {
+        BatchVerifier {
+            alpha_g1_beta_g2: bls12_381::pairing(&vk.alpha_g1, &vk.beta_g2),
+            neg_gamma_g2: G2Prepared::from(-vk.gamma_g2),
+            neg_delta_g2: G2Prepared::from(-vk.delta_g2),
+            ic: vk.ic.clone(),
+            pvk: groth16::prepare_verifying_key(vk),
+        }
+    }
+
+    /// The prepared key used to check proofs one at a time.
+    pub fn prepared_verifying_key(&self) -> &PreparedVerifyingKey<Bls12> {
+        &self.pvk
+    }
+
+    /// Checks the proofs at `indices` together. Every proof must have the
+    /// right number of public inputs.
+    fn check<R: RngCore>(
+        &self,
+        proofs: &[(Proof<Bls12>, Vec<Scalar>)],
+        indices: &[usize],
+        rng: &mut R,
+    ) -> bool {
+        let mut weight_sum = Scalar::zero();
+        // Weights of the IC points, summed over the batch.
+        let mut ic_weights = vec![Scalar::zero(); self.ic.len()];
+        let mut c = G1Projective::identity();
+        let mut a = Vec::with_capacity(indices.len());
+        for &i in indices {
+            let (proof, inputs) = &proofs[i];
+            let r = Scalar::random(&mut *rng);
+            weight_sum += r;
+            ic_weights[0] += r;
+            for (weight, input) in ic_weights[1..].iter_mut().zip(inputs) {
+                *weight += r * input;
+            }
+            c += proof.c * r;
+            a.push(proof.a * r);
+        }
+
+        let l = self
+            .ic
+            .iter()
+            .zip(&ic_weights)
+            .fold(G1Projective::identity(), |acc, (ic, weight)| {
+                acc + ic * weight
+            });
+
+        let mut a_affine = vec![G1Affine::identity(); a.len()];
+        G1Projective::batch_normalize(&a, &mut a_affine);
+        let b = indices
+            .iter()
+            .map(|&i| G2Prepared::from(proofs[i].0.b))
+            .collect::<Vec<_>>();
+        let l = G1Affine::from(l);
+        let c = G1Affine::from(c);
+
+        let mut terms = a_affine.iter().zip(&b).collect::<Vec<_>>();
+        terms.push((&l, &self.neg_gamma_g2));
+        terms.push((&c, &self.neg_delta_g2));
+        multi_miller_loop(&terms).final_exponentiation() == self.alpha_g1_beta_g2 * weight_sum
+    }
+
+    /// Appends the invalid proofs among `indices`, which failed as a batch,
+    /// to `invalid`.
+    fn bisect<R: RngCore>(
+        &self,
+        proofs: &[(Proof<Bls12>, Vec<Scalar>)],
+        indices: &[usize],
+        rng: &mut R,
+        invalid: &mut Vec<usize>,
+    ) {
+        if let [i] = *indices {
+            let (proof, inputs) = &proofs[i];
+            if groth16::verify_proof(&self.pvk, proof, inputs).is_err() {
+                invalid.push(i);
+            }
+            return;
+        }
+        let (left, right) = indices.split_at(indices.len() / 2);
+        for half in &[left, right] {
+            if !self.check(proofs, half, rng) {
+                self.bisect(proofs, half, rng, invalid);
+            }
+        }
+    }
+
+    /// Verifies every proof against its public inputs. On failure, returns
+    /// [`Error::InvalidProofs`] with the indices of exactly the invalid
+    /// proofs, in increasing order.
+    pub fn verify<R: RngCore>(
+        &self,
+        proofs: &[(Proof<Bls12>, Vec<Scalar>)],
+        rng: &mut R,
+    ) -> Result<()> {
+        let (indices, mut invalid): (Vec<usize>, Vec<usize>) =
+            (0..proofs.len()).partition(|&i| proofs[i].1.len() + 1 == self.ic.len());
+
+        if !indices.is_empty() && !self.check(proofs, &indices, rng) {
+            self.bisect(proofs, &indices, rng, &mut invalid);
+        }
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            invalid.sort_unstable();
+            Err(Error::InvalidProofs(invalid))
+        }
+    }
+
+    /// Verifies proof bundles, see [`BatchVerifier::verify`]. The caller
+    /// checks that the bundles were made for this key, and `output_size` is
+    /// that of its configuration. Fails with [`Error::InvalidLength`] before
+    /// checking any proof if a hash doesn't have `output_size` bytes.
+    pub fn verify_bundles<R: RngCore>(
+        &self,
+        bundles: &[ProofBundle],
+        output_size: usize,
+        rng: &mut R,
+    ) -> Result<()> {
+        let proofs = bundles
+            .iter()
+            .map(|bundle| Ok((bundle.proof.clone(), bundle.public_inputs(output_size)?)))
+            .collect::<Result<Vec<_>>>()?;
+        self.verify(&proofs, rng)
+    }
+}
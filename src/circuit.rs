@@ -0,0 +1,222 @@
+use bellman::{
+    gadgets::{
+        boolean::{AllocatedBit, Boolean},
+        multipack,
+    },
+    Circuit, ConstraintSystem, SynthesisError,
+};
+use ff::PrimeField;
+use std::{fmt, str::FromStr};
+
+use crate::{
+    cs::ShapeHasher,
+    gadget::{mask_prefix, xor_fold, xor_fold_lc},
+    Error, Result, INPUT_SIZE, MAX_INPUT_SIZE, MAX_OUTPUT_SIZE,
+};
+
+/// Implementation of the xor-fold inside the circuit. Both compute the same
+/// digest, but produce different constraint systems, so they need different
+/// parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XorGadget {
+    /// A chain of `Boolean::xor`, one constraint per input bit, see
+    /// [`xor_fold`].
+    Boolean,
+    /// Parity of a linear combination of input bits, see [`xor_fold_lc`].
+    Linear,
+}
+
+impl XorGadget {
+    pub const ALL: [XorGadget; 2] = [XorGadget::Boolean, XorGadget::Linear];
+}
+
+impl fmt::Display for XorGadget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            XorGadget::Boolean => "boolean",
+            XorGadget::Linear => "linear",
+        })
+    }
+}
+
+impl FromStr for XorGadget {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "boolean" => Ok(XorGadget::Boolean),
+            "linear" => Ok(XorGadget::Linear),
+            _ => Err(format!(
+                "unknown gadget {:?}, expected boolean or linear",
+                s
+            )),
+        }
+    }
+}
+
+/// Parameters of the circuit that are fixed at setup. Parameters generated
+/// for one configuration can't be used to prove or verify another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitConfig {
+    /// Size of the preimage in bytes, or the maximum size if
+    /// `variable_length` is set.
+    pub input_size: usize,
+    /// Accept preimages of any length up to `input_size`, keeping the actual
+    /// length private.
+    pub variable_length: bool,
+    /// Size of the digest in bytes, see [`xor_fold`].
+    pub output_size: usize,
+    pub gadget: XorGadget,
+}
+
+impl Default for CircuitConfig {
+    fn default() -> Self {
+        CircuitConfig {
+            input_size: INPUT_SIZE,
+            variable_length: false,
+            output_size: 1,
+            gadget: XorGadget::Boolean,
+        }
+    }
+}
+
+impl CircuitConfig {
+    /// Checks that the configuration describes a circuit: the digest must
+    /// have at least one byte, and neither size may exceed
+    /// [`MAX_INPUT_SIZE`] and [`MAX_OUTPUT_SIZE`].
+    pub fn check(&self) -> Result<()> {
+        if self.input_size > MAX_INPUT_SIZE {
+            return Err(Error::InvalidLength {
+                what: "preimage bytes",
+                min: 0,
+                max: MAX_INPUT_SIZE,
+                got: self.input_size,
+            });
+        }
+        if self.output_size == 0 || self.output_size > MAX_OUTPUT_SIZE {
+            return Err(Error::InvalidLength {
+                what: "digest bytes",
+                min: 1,
+                max: MAX_OUTPUT_SIZE,
+                got: self.output_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that `preimage` has a length this configuration accepts.
+    pub fn check_preimage(&self, preimage: &[u8]) -> Result<()> {
+        let min = if self.variable_length {
+            0
+        } else {
+            self.input_size
+        };
+        if preimage.len() < min || preimage.len() > self.input_size {
+            return Err(Error::InvalidLength {
+                what: "preimage bytes",
+                min,
+                max: self.input_size,
+                got: preimage.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Synthesizes [`MyCircuit`] with this configuration into a
+    /// [`ShapeHasher`], which also counts constraints and variables.
+    pub fn shape(&self) -> Result<ShapeHasher> {
+        self.check()?;
+        let mut cs = ShapeHasher::new();
+        Circuit::<bls12_381::Scalar>::synthesize(MyCircuit::new(*self), &mut cs)?;
+        Ok(cs)
+    }
+
+    /// Digest of the constraint system of [`MyCircuit`] with this
+    /// configuration, see [`ShapeHasher`].
+    pub fn digest(&self) -> Result<[u8; 32]> {
+        Ok(self.shape()?.finish())
+    }
+}
+
+/// Circuit proving knowledge of a preimage of the xor-fold hash. The digest
+/// bits are packed into the public inputs.
+pub struct MyCircuit {
+    pub config: CircuitConfig,
+    /// The input to xor-sum we are proving that we know. Set to `None` when we
+    /// are verifying a proof (and do not have the witness data). Synthesis
+    /// fails unless it has a length accepted by
+    /// [`CircuitConfig::check_preimage`].
+    pub preimage: Option<Vec<u8>>,
+}
+
+impl MyCircuit {
+    /// Circuit without a witness, for the setup.
+    pub fn new(config: CircuitConfig) -> Self {
+        MyCircuit {
+            config,
+            preimage: None,
+        }
+    }
+
+    /// Circuit with `preimage` as a witness, for proving.
+    pub fn with_preimage(config: CircuitConfig, preimage: Vec<u8>) -> Self {
+        MyCircuit {
+            config,
+            preimage: Some(preimage),
+        }
+    }
+}
+
+impl<Scalar: PrimeField> Circuit<Scalar> for MyCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        self,
+        cs: &mut CS,
+    ) -> std::result::Result<(), SynthesisError> {
+        let input_size = self.config.input_size;
+        let length = self.preimage.as_ref().map(Vec::len);
+
+        // Compute the values for the bits of the preimage. If we are verifying a proof,
+        // we still need to create the same constraints, so we return an equivalent-size
+        // Vec of None (indicating that the value of each bit is unknown).
+        let bit_values = if let Some(mut preimage) = self.preimage {
+            self.config.check_preimage(&preimage)?;
+            // Pad variable-length preimages with zeros, the padding is masked
+            // out below anyway.
+            preimage.resize(input_size, 0);
+            preimage
+                .iter()
+                .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1u8 == 1u8))
+                .map(Some)
+                .collect()
+        } else {
+            vec![None; input_size * 8]
+        };
+
+        let preimage_bits = bit_values
+            .into_iter()
+            .enumerate()
+            .map(|(i, b)| AllocatedBit::alloc(cs.namespace(|| format!("preimage bit {}", i)), b))
+            .map(|b| b.map(Boolean::from))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let preimage_bits = if self.config.variable_length {
+            mask_prefix(cs.namespace(|| "mask(preimage)"), &preimage_bits, length)?
+        } else {
+            preimage_bits
+        };
+
+        let fold = match self.config.gadget {
+            XorGadget::Boolean => xor_fold,
+            XorGadget::Linear => xor_fold_lc,
+        };
+        let hash = fold(
+            cs.namespace(|| "xor_sum(preimage)"),
+            &preimage_bits,
+            input_size,
+            self.config.output_size,
+        )?;
+
+        // Expose the hash bits as the public input (packed into field elements).
+        multipack::pack_into_inputs(cs.namespace(|| "pack hash"), &hash)
+    }
+}
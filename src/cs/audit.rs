@@ -0,0 +1,281 @@
+use std::{collections::BTreeMap, fmt};
+
+use bellman::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use ff::PrimeField;
+
+/// Name that `AllocatedBit::alloc` gives to its variable. Variables with this
+/// name are expected to be boolean constrained.
+pub const BOOLEAN_NAME: &str = "boolean";
+
+/// Constraint system that records the structure of a circuit and looks for
+/// signs of under-constrained synthesis, see [`AuditReport`]. Like
+/// [`ShapeHasher`](super::ShapeHasher), it never evaluates assignments.
+pub struct Auditor<Scalar: PrimeField> {
+    namespace: Vec<String>,
+    /// Paths of input variables, the first one is "one".
+    inputs: Vec<String>,
+    aux: Vec<String>,
+    /// Variables of every constraint with non-zero coefficients, per A, B, C.
+    constraints: Vec<[BTreeMap<Var, Scalar>; 3]>,
+    boolean_names: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Var {
+    Input(usize),
+    Aux(usize),
+}
+
+/// Problems found by an [`Auditor`]. Every entry is the path of a variable.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Auxiliary variables that don't appear in any constraint, so the
+    /// prover can assign them anything.
+    pub unconstrained: Vec<String>,
+    /// Auxiliary variables that appear in constraints, but none of them
+    /// connects (even transitively) to a public input. Whatever they compute
+    /// can't influence what the verifier sees.
+    pub disconnected: Vec<String>,
+    /// Variables allocated as booleans that never get a constraint of the
+    /// form `x * (1 - x) = 0`.
+    pub unchecked_booleans: Vec<String>,
+}
+
+impl AuditReport {
+    pub fn is_clean(&self) -> bool {
+        self.unconstrained.is_empty()
+            && self.disconnected.is_empty()
+            && self.unchecked_booleans.is_empty()
+    }
+}
+
+impl fmt::Display for AuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return writeln!(f, "no problems found");
+        }
+
+        let sections = [
+            ("unconstrained variables", &self.unconstrained),
+            ("variables not tied to public inputs", &self.disconnected),
+            (
+                "booleans without a boolean constraint",
+                &self.unchecked_booleans,
+            ),
+        ];
+        for (title, paths) in sections.iter() {
+            if paths.is_empty() {
+                continue;
+            }
+            writeln!(f, "{} ({}):", title, paths.len())?;
+            for path in paths.iter() {
+                writeln!(f, "  {}", path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Synthesizes `circuit` without a witness and audits it.
+pub fn audit<Scalar: PrimeField, C: Circuit<Scalar>>(circuit: C) -> crate::Result<AuditReport> {
+    let mut cs = Auditor::new();
+    circuit.synthesize(&mut cs)?;
+    Ok(cs.report())
+}
+
+impl<Scalar: PrimeField> Auditor<Scalar> {
+    pub fn new() -> Self {
+        Auditor {
+            namespace: vec![],
+            inputs: vec!["ONE".to_string()],
+            aux: vec![],
+            constraints: vec![],
+            boolean_names: vec![BOOLEAN_NAME.to_string()],
+        }
+    }
+
+    /// Also expect variables named `name` (the last component of the path)
+    /// to be boolean constrained, for gadgets that allocate bits by hand.
+    pub fn with_boolean_name<N: Into<String>>(mut self, name: N) -> Self {
+        self.boolean_names.push(name.into());
+        self
+    }
+
+    fn path(&self, name: String) -> String {
+        let mut path = self.namespace.join("/");
+        if !path.is_empty() {
+            path.push('/');
+        }
+        path + &name
+    }
+
+    /// Checks everything synthesized so far.
+    pub fn report(&self) -> AuditReport {
+        let mut report = AuditReport::default();
+
+        // Union-find over all variables, joining the ones that share a
+        // constraint. "One" is left out: it's in almost every constraint, but
+        // it's a constant and carries no information.
+        let num_vars = self.inputs.len() + self.aux.len();
+        let index = |var: Var| match var {
+            Var::Input(i) => i,
+            Var::Aux(i) => self.inputs.len() + i,
+        };
+        let mut parent = (0..num_vars).collect::<Vec<_>>();
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        let mut constrained = vec![false; num_vars];
+        for constraint in &self.constraints {
+            let vars = constraint
+                .iter()
+                .flat_map(|lc| lc.keys())
+                .filter(|&&var| var != Var::Input(0))
+                .map(|&var| index(var))
+                .collect::<Vec<_>>();
+            for &var in &vars {
+                constrained[var] = true;
+            }
+            for pair in vars.windows(2) {
+                let (a, b) = (find(&mut parent, pair[0]), find(&mut parent, pair[1]));
+                parent[a] = b;
+            }
+        }
+
+        let mut public = vec![false; num_vars];
+        for i in 1..self.inputs.len() {
+            let root = find(&mut parent, i);
+            public[root] = true;
+        }
+
+        for (i, path) in self.aux.iter().enumerate() {
+            let var = index(Var::Aux(i));
+            if !constrained[var] {
+                report.unconstrained.push(path.clone());
+            } else if !public[find(&mut parent, var)] {
+                report.disconnected.push(path.clone());
+            }
+        }
+
+        let mut checked = vec![false; num_vars];
+        for constraint in &self.constraints {
+            if let Some(var) = boolean_constraint(constraint) {
+                checked[index(var)] = true;
+            }
+        }
+        for (i, path) in self.aux.iter().enumerate() {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            if self.boolean_names.iter().any(|b| b == name) && !checked[index(Var::Aux(i))] {
+                report.unchecked_booleans.push(path.clone());
+            }
+        }
+
+        report
+    }
+}
+
+impl<Scalar: PrimeField> Default for Auditor<Scalar> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// If the constraint is `x * (1 - x) = 0` or `(1 - x) * x = 0` (up to a
+/// scaling of either side), returns `x`.
+fn boolean_constraint<Scalar: PrimeField>(constraint: &[BTreeMap<Var, Scalar>; 3]) -> Option<Var> {
+    let [a, b, c] = constraint;
+    if !c.is_empty() {
+        return None;
+    }
+
+    // `lc` is `k * x` for a single non-"one" variable.
+    let single = |lc: &BTreeMap<Var, Scalar>| match lc.iter().collect::<Vec<_>>()[..] {
+        [(&var, _)] if var != Var::Input(0) => Some(var),
+        _ => None,
+    };
+    // `lc` is `k * (1 - x)`. Terms are sorted, so "one" always comes first.
+    let one_minus = |lc: &BTreeMap<Var, Scalar>| match lc.iter().collect::<Vec<_>>()[..] {
+        [(&Var::Input(0), one), (&var, coeff)] if *coeff == one.neg() => Some(var),
+        _ => None,
+    };
+
+    match (single(a), one_minus(b), one_minus(a), single(b)) {
+        (Some(x), Some(y), _, _) | (_, _, Some(x), Some(y)) if x == y => Some(x),
+        _ => None,
+    }
+}
+
+fn terms<Scalar: PrimeField>(lc: LinearCombination<Scalar>) -> BTreeMap<Var, Scalar> {
+    let mut terms = BTreeMap::new();
+    for (var, coeff) in lc.as_ref() {
+        let var = match var.get_unchecked() {
+            Index::Input(i) => Var::Input(i),
+            Index::Aux(i) => Var::Aux(i),
+        };
+        *terms.entry(var).or_insert_with(Scalar::zero) += coeff;
+    }
+    terms.retain(|_, coeff| !coeff.is_zero());
+    terms
+}
+
+impl<Scalar: PrimeField> ConstraintSystem<Scalar> for Auditor<Scalar> {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, annotation: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let path = self.path(annotation().into());
+        self.aux.push(path);
+        Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, annotation: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let path = self.path(annotation().into());
+        self.inputs.push(path);
+        Ok(Variable::new_unchecked(Index::Input(self.inputs.len() - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+        self.constraints.push([
+            terms(a(LinearCombination::zero())),
+            terms(b(LinearCombination::zero())),
+            terms(c(LinearCombination::zero())),
+        ]);
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespace.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self) {
+        self.namespace.pop();
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
@@ -0,0 +1,11 @@
+//! Custom constraint systems used to inspect circuits without proving them.
+
+mod audit;
+mod record;
+mod shape;
+mod stats;
+
+pub use audit::{audit, AuditReport, Auditor, BOOLEAN_NAME};
+pub use record::{record, Recorder};
+pub use shape::ShapeHasher;
+pub use stats::{stats, CircuitStats, NamespaceStats, StatsCollector};
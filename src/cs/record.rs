@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+
+use bellman::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use ff::PrimeField;
+
+use crate::r1cs::{Constraint, R1cs};
+
+/// Constraint system that records the constraint matrices of a circuit and,
+/// if it is synthesized with a witness, the assignment of every variable.
+pub struct Recorder<Scalar: PrimeField> {
+    inputs: Vec<Option<Scalar>>,
+    aux: Vec<Option<Scalar>>,
+    constraints: Vec<[Vec<(Index, Scalar)>; 3]>,
+}
+
+/// Synthesizes `circuit` and records its constraint system, along with the
+/// witness if the circuit has one.
+pub fn record<Scalar: PrimeField, C: Circuit<Scalar>>(
+    circuit: C,
+) -> crate::Result<(R1cs<Scalar>, Option<Vec<Scalar>>)> {
+    let mut cs = Recorder::new();
+    circuit.synthesize(&mut cs)?;
+    Ok((cs.r1cs(), cs.witness()))
+}
+
+impl<Scalar: PrimeField> Recorder<Scalar> {
+    pub fn new() -> Self {
+        Recorder {
+            inputs: vec![Some(Scalar::one())],
+            aux: vec![],
+            constraints: vec![],
+        }
+    }
+
+    /// Wire of a variable, in the iden3 order: "one", public inputs, then
+    /// everything else.
+    fn wire(&self, index: Index) -> usize {
+        match index {
+            Index::Input(i) => i,
+            Index::Aux(i) => self.inputs.len() + i,
+        }
+    }
+
+    /// The recorded constraint system.
+    pub fn r1cs(&self) -> R1cs<Scalar> {
+        let constraints = self
+            .constraints
+            .iter()
+            .map(|[a, b, c]| {
+                let wires = |lc: &Vec<(Index, Scalar)>| {
+                    lc.iter()
+                        .map(|&(index, coeff)| (self.wire(index), coeff))
+                        .collect()
+                };
+                Constraint {
+                    a: wires(a),
+                    b: wires(b),
+                    c: wires(c),
+                }
+            })
+            .collect();
+
+        R1cs {
+            num_public: self.inputs.len() - 1,
+            num_private: self.aux.len(),
+            constraints,
+        }
+    }
+
+    /// The assignment of every wire, if every variable has one.
+    pub fn witness(&self) -> Option<Vec<Scalar>> {
+        self.inputs.iter().chain(self.aux.iter()).copied().collect()
+    }
+}
+
+impl<Scalar: PrimeField> Default for Recorder<Scalar> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Evaluates an assignment, which is missing when there is no witness.
+fn assignment<Scalar, F>(f: F) -> Result<Option<Scalar>, SynthesisError>
+where
+    F: FnOnce() -> Result<Scalar, SynthesisError>,
+{
+    match f() {
+        Ok(value) => Ok(Some(value)),
+        Err(SynthesisError::AssignmentMissing) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Merges terms per variable and drops zero coefficients. The terms come out
+/// sorted in wire order.
+fn terms<Scalar: PrimeField>(lc: LinearCombination<Scalar>) -> Vec<(Index, Scalar)> {
+    let mut terms = BTreeMap::new();
+    for (var, coeff) in lc.as_ref() {
+        let key = match var.get_unchecked() {
+            Index::Input(i) => (0u8, i),
+            Index::Aux(i) => (1u8, i),
+        };
+        *terms.entry(key).or_insert_with(Scalar::zero) += coeff;
+    }
+    terms
+        .into_iter()
+        .filter(|(_, coeff)| !coeff.is_zero())
+        .map(|((kind, i), coeff)| {
+            let index = if kind == 0 {
+                Index::Input(i)
+            } else {
+                Index::Aux(i)
+            };
+            (index, coeff)
+        })
+        .collect()
+}
+
+impl<Scalar: PrimeField> ConstraintSystem<Scalar> for Recorder<Scalar> {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.aux.push(assignment(f)?);
+        Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inputs.push(assignment(f)?);
+        Ok(Variable::new_unchecked(Index::Input(self.inputs.len() - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+        self.constraints.push([
+            terms(a(LinearCombination::zero())),
+            terms(b(LinearCombination::zero())),
+            terms(c(LinearCombination::zero())),
+        ]);
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
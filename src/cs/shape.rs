@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use bellman::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use blake2s_simd::{Params as Blake2sParams, State as Blake2sState};
+use ff::PrimeField;
+
+/// Constraint system that hashes the shape of a circuit: the number of
+/// variables and every constraint with its coefficients. Variable assignments
+/// are never evaluated, so circuits can be synthesized without a witness.
+///
+/// Two circuits with the same digest produce the same Groth16 parameters
+/// (given the same randomness), so the digest identifies which circuit a
+/// parameter file was generated for. Names and namespaces don't affect it.
+pub struct ShapeHasher {
+    num_inputs: usize,
+    num_aux: usize,
+    num_constraints: usize,
+    constraints: Blake2sState,
+}
+
+impl ShapeHasher {
+    pub fn new() -> Self {
+        ShapeHasher {
+            // The "one" input is always allocated.
+            num_inputs: 1,
+            num_aux: 0,
+            num_constraints: 0,
+            constraints: Blake2sParams::new().hash_length(32).to_state(),
+        }
+    }
+
+    /// Number of input variables, including the "one" input.
+    pub fn num_inputs(&self) -> usize {
+        self.num_inputs
+    }
+
+    /// Number of auxiliary variables.
+    pub fn num_aux(&self) -> usize {
+        self.num_aux
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+
+    /// Returns the digest of everything synthesized so far.
+    pub fn finish(&self) -> [u8; 32] {
+        let mut h = Blake2sParams::new().hash_length(32).to_state();
+        h.update(&(self.num_inputs as u64).to_be_bytes());
+        h.update(&(self.num_aux as u64).to_be_bytes());
+        h.update(&(self.num_constraints as u64).to_be_bytes());
+        h.update(self.constraints.finalize().as_bytes());
+
+        let mut digest = [0; 32];
+        digest.copy_from_slice(h.finalize().as_bytes());
+        digest
+    }
+}
+
+impl Default for ShapeHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hashes a linear combination in a canonical form: terms are merged per
+/// variable, sorted, and zero coefficients are dropped.
+fn hash_lc<Scalar: PrimeField>(lc: &LinearCombination<Scalar>, h: &mut Blake2sState) {
+    let mut terms = BTreeMap::new();
+    for (var, coeff) in lc.as_ref() {
+        let key = match var.get_unchecked() {
+            Index::Input(i) => (0u8, i),
+            Index::Aux(i) => (1u8, i),
+        };
+        *terms.entry(key).or_insert_with(Scalar::zero) += coeff;
+    }
+    terms.retain(|_, coeff| !coeff.is_zero());
+
+    h.update(&(terms.len() as u64).to_be_bytes());
+    for ((kind, index), coeff) in terms {
+        h.update(&[kind]);
+        h.update(&(index as u64).to_be_bytes());
+        h.update(coeff.to_repr().as_ref());
+    }
+}
+
+impl<Scalar: PrimeField> ConstraintSystem<Scalar> for ShapeHasher {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_aux += 1;
+        Ok(Variable::new_unchecked(Index::Aux(self.num_aux - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_inputs += 1;
+        Ok(Variable::new_unchecked(Index::Input(self.num_inputs - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+        self.num_constraints += 1;
+        hash_lc(&a(LinearCombination::zero()), &mut self.constraints);
+        hash_lc(&b(LinearCombination::zero()), &mut self.constraints);
+        hash_lc(&c(LinearCombination::zero()), &mut self.constraints);
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
@@ -0,0 +1,203 @@
+use std::{collections::BTreeMap, fmt};
+
+use bellman::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use ff::PrimeField;
+use serde::Serialize;
+
+/// Constraint system that counts constraints and variables, in total and per
+/// namespace. Assignments are never evaluated.
+///
+/// Namespaces are grouped by their first `depth` components, with every run of
+/// digits replaced by `i`, so that `preimage bit 0` ... `preimage bit 255`
+/// end up in a single `preimage bit i` group.
+pub struct StatsCollector {
+    depth: usize,
+    namespace: Vec<String>,
+    stats: CircuitStats,
+    groups: BTreeMap<String, NamespaceStats>,
+}
+
+/// Cost of a whole circuit.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CircuitStats {
+    pub constraints: usize,
+    pub aux: usize,
+    /// Input variables, including the constant "one".
+    pub inputs: usize,
+    /// Breakdown per namespace group, sorted by name.
+    pub namespaces: Vec<NamespaceStats>,
+}
+
+/// Cost of everything synthesized inside a group of namespaces.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct NamespaceStats {
+    /// The group name, empty for objects created outside of any namespace.
+    pub name: String,
+    pub constraints: usize,
+    pub aux: usize,
+    pub inputs: usize,
+}
+
+/// Synthesizes `circuit` without a witness and counts its cost, grouping
+/// namespaces by their first `depth` components.
+pub fn stats<Scalar: PrimeField, C: Circuit<Scalar>>(
+    circuit: C,
+    depth: usize,
+) -> crate::Result<CircuitStats> {
+    let mut cs = StatsCollector::new(depth);
+    circuit.synthesize(&mut cs)?;
+    Ok(cs.finish())
+}
+
+/// Replaces every run of digits in `name` with `i`.
+fn generalize(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut in_number = false;
+    for c in name.chars() {
+        if c.is_ascii_digit() {
+            if !in_number {
+                out.push('i');
+            }
+            in_number = true;
+        } else {
+            out.push(c);
+            in_number = false;
+        }
+    }
+    out
+}
+
+impl StatsCollector {
+    pub fn new(depth: usize) -> Self {
+        StatsCollector {
+            depth,
+            namespace: vec![],
+            stats: CircuitStats {
+                inputs: 1,
+                ..CircuitStats::default()
+            },
+            groups: BTreeMap::new(),
+        }
+    }
+
+    fn group(&mut self) -> &mut NamespaceStats {
+        let name = self
+            .namespace
+            .iter()
+            .take(self.depth)
+            .map(|ns| generalize(ns))
+            .collect::<Vec<_>>()
+            .join("/");
+        self.groups
+            .entry(name.clone())
+            .or_insert_with(|| NamespaceStats {
+                name,
+                ..NamespaceStats::default()
+            })
+    }
+
+    /// Returns the statistics of everything synthesized so far.
+    pub fn finish(self) -> CircuitStats {
+        CircuitStats {
+            namespaces: self.groups.into_values().collect(),
+            ..self.stats
+        }
+    }
+}
+
+impl fmt::Display for CircuitStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "constraints: {}", self.constraints)?;
+        writeln!(f, "aux variables: {}", self.aux)?;
+        writeln!(f, "input variables: {} (including \"one\")", self.inputs)?;
+        writeln!(f)?;
+
+        let width = self
+            .namespaces
+            .iter()
+            .map(|ns| ns.name.len())
+            .chain(Some("namespace".len()))
+            .max()
+            .unwrap_or(0);
+        writeln!(
+            f,
+            "{:<width$} {:>12} {:>10} {:>8}",
+            "namespace",
+            "constraints",
+            "aux vars",
+            "inputs",
+            width = width
+        )?;
+        for ns in &self.namespaces {
+            let name = if ns.name.is_empty() {
+                "<root>"
+            } else {
+                &ns.name
+            };
+            writeln!(
+                f,
+                "{:<width$} {:>12} {:>10} {:>8}",
+                name,
+                ns.constraints,
+                ns.aux,
+                ns.inputs,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<Scalar: PrimeField> ConstraintSystem<Scalar> for StatsCollector {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.stats.aux += 1;
+        self.group().aux += 1;
+        Ok(Variable::new_unchecked(Index::Aux(self.stats.aux - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.stats.inputs += 1;
+        self.group().inputs += 1;
+        Ok(Variable::new_unchecked(Index::Input(self.stats.inputs - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, _: LA, _: LB, _: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+        self.stats.constraints += 1;
+        self.group().constraints += 1;
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespace.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self) {
+        self.namespace.pop();
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
@@ -0,0 +1,46 @@
+//! Conversions between little-endian byte strings and decimal strings, the
+//! encoding of field elements in the JSON files of snarkjs.
+
+/// Converts a little-endian number to decimal.
+pub(crate) fn le_to_decimal(bytes: &[u8]) -> String {
+    let mut digits = vec![];
+    let mut n = bytes.to_vec();
+    while n.iter().any(|&b| b != 0) {
+        // Divide by 10, starting from the most significant byte.
+        let mut rem = 0u32;
+        for byte in n.iter_mut().rev() {
+            let cur = (rem << 8) | u32::from(*byte);
+            *byte = (cur / 10) as u8;
+            rem = cur % 10;
+        }
+        digits.push(b'0' + rem as u8);
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.reverse();
+    String::from_utf8(digits).expect("digits are ASCII")
+}
+
+/// Converts a decimal number to `len` little-endian bytes, or `None` if it
+/// isn't a decimal number or doesn't fit.
+pub(crate) fn decimal_to_le(s: &str, len: usize) -> Option<Vec<u8>> {
+    if s.is_empty() {
+        return None;
+    }
+    let mut n = vec![0u8; len];
+    for c in s.chars() {
+        let mut carry = c.to_digit(10)?;
+        // Multiply by 10 and add the digit, starting from the least
+        // significant byte.
+        for byte in n.iter_mut() {
+            let cur = u32::from(*byte) * 10 + carry;
+            *byte = cur as u8;
+            carry = cur >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(n)
+}
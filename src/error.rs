@@ -0,0 +1,129 @@
+use std::{error, fmt, io};
+
+use bellman::{SynthesisError, VerificationError};
+
+/// Errors of this crate.
+///
+/// bellman's own errors are only ever formatted with `Debug`: their `Display`
+/// implementations call themselves and overflow the stack.
+#[derive(Debug)]
+pub enum Error {
+    /// Synthesizing the circuit failed.
+    Synthesis(SynthesisError),
+    /// An input doesn't have a length this configuration accepts: between
+    /// `min` and `max` of `what`.
+    InvalidLength {
+        what: &'static str,
+        min: usize,
+        max: usize,
+        got: usize,
+    },
+    /// Reading or writing failed.
+    Io(io::Error),
+    /// Malformed, truncated or inconsistent encoded data.
+    Decode(String),
+    /// The proof doesn't verify.
+    Verification(VerificationError),
+    /// The proofs at these indices of a batch don't verify.
+    InvalidProofs(Vec<usize>),
+    /// A ceremony transcript doesn't verify.
+    Ceremony(String),
+}
+
+/// Result type of this crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// An input should have exactly `expected` of `what`.
+    pub(crate) fn length(what: &'static str, expected: usize, got: usize) -> Self {
+        Error::InvalidLength {
+            what,
+            min: expected,
+            max: expected,
+            got,
+        }
+    }
+
+    pub(crate) fn decode<S: Into<String>>(msg: S) -> Self {
+        Error::Decode(msg.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Synthesis(e) => write!(f, "synthesis failed: {:?}", e),
+            Error::InvalidLength {
+                what,
+                min,
+                max,
+                got,
+            } => {
+                if min == max {
+                    write!(f, "expected {} {}, got {}", min, what, got)
+                } else if *max == usize::MAX {
+                    write!(f, "expected at least {} {}, got {}", min, what, got)
+                } else if *min == 0 {
+                    write!(f, "expected at most {} {}, got {}", max, what, got)
+                } else {
+                    write!(f, "expected {} to {} {}, got {}", min, max, what, got)
+                }
+            }
+            Error::Io(e) => e.fmt(f),
+            Error::Decode(msg) => f.write_str(msg),
+            Error::Verification(e) => write!(f, "verification failed: {:?}", e),
+            Error::InvalidProofs(indices) => write!(f, "invalid proofs at {:?}", indices),
+            Error::Ceremony(msg) => write!(f, "invalid ceremony: {}", msg),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// bellman, and the readers of this crate, report malformed encodings as
+/// `InvalidData` and truncated ones as `UnexpectedEof`: those are decoding
+/// failures, not I/O failures.
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+                Error::Decode(e.to_string())
+            }
+            _ => Error::Io(e),
+        }
+    }
+}
+
+impl From<SynthesisError> for Error {
+    fn from(e: SynthesisError) -> Self {
+        Error::Synthesis(e)
+    }
+}
+
+impl From<VerificationError> for Error {
+    fn from(e: VerificationError) -> Self {
+        Error::Verification(e)
+    }
+}
+
+/// Lets gadgets returning [`Error`] be used in `Circuit::synthesize`. Errors
+/// other than synthesis errors become `SynthesisError::IoError`, the only
+/// variant that carries a message.
+impl From<Error> for SynthesisError {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Synthesis(e) => e,
+            Error::Io(e) => SynthesisError::IoError(e),
+            e => {
+                SynthesisError::IoError(io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+            }
+        }
+    }
+}
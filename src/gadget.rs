@@ -0,0 +1,193 @@
+use bellman::{
+    gadgets::boolean::{AllocatedBit, Boolean},
+    ConstraintSystem, LinearCombination, SynthesisError,
+};
+use ff::PrimeField;
+
+use crate::{Error, Result};
+
+/// Checks the arguments shared by the xor gadgets.
+fn check_sizes(data: &[Boolean], input_size: usize, output_size: usize) -> Result<()> {
+    if data.len() != input_size * 8 {
+        return Err(Error::length("data bits", input_size * 8, data.len()));
+    }
+    if output_size == 0 {
+        return Err(Error::InvalidLength {
+            what: "digest bytes",
+            min: 1,
+            max: usize::MAX,
+            got: 0,
+        });
+    }
+    Ok(())
+}
+
+/// xor-sum gadget. Data should have `input_size` bytes or `input_size * 8`
+/// boolean elements, otherwise it fails with [`Error::InvalidLength`].
+pub fn xor_sum<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+    cs: CS,
+    data: &[Boolean],
+    input_size: usize,
+) -> Result<Vec<Boolean>> {
+    xor_fold(cs, data, input_size, 1)
+}
+
+/// xor-fold gadget: byte `i` of the data is XORed into byte `i % output_size`
+/// of the digest, so the result has `output_size * 8` boolean elements. Data
+/// should have `input_size` bytes or `input_size * 8` boolean elements
+pub fn xor_fold<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+    mut cs: CS,
+    data: &[Boolean],
+    input_size: usize,
+    output_size: usize,
+) -> Result<Vec<Boolean>> {
+    check_sizes(data, input_size, output_size)?;
+
+    let mut digest = vec![Boolean::Constant(false); output_size * 8];
+    for (i, chunk) in data.chunks(8).enumerate() {
+        let lane = i % output_size;
+        let byte = &mut digest[lane * 8..(lane + 1) * 8];
+        for (j, (a, b)) in byte.iter_mut().zip(chunk.iter()).enumerate() {
+            *a = Boolean::xor(cs.namespace(|| format!("xor [{}][{}]", i, j)), a, b)?;
+        }
+    }
+
+    Ok(digest)
+}
+
+/// Same as [`xor_fold`], but each digest bit is computed as the parity of the
+/// sum of all input bits folded into it, instead of a chain of XORs.
+///
+/// For a digest bit that sums `n` input bits, the gadget witnesses the parity
+/// `p` and the quotient `q = n / 2` as `log2(n / 2) + 1` bits and enforces
+/// `sum = p + 2 * q`. That costs `O(log n)` constraints per digest bit, instead
+/// of one constraint per input bit. The sum never exceeds the number of input
+/// bits, so it can't wrap around the field modulus and `p` is the parity.
+pub fn xor_fold_lc<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+    mut cs: CS,
+    data: &[Boolean],
+    input_size: usize,
+    output_size: usize,
+) -> Result<Vec<Boolean>> {
+    check_sizes(data, input_size, output_size)?;
+
+    let mut digest = Vec::with_capacity(output_size * 8);
+    for lane in 0..output_size {
+        for j in 0..8 {
+            let bits = (lane..input_size)
+                .step_by(output_size)
+                .map(|i| &data[i * 8 + j])
+                .collect::<Vec<_>>();
+
+            let bit = match bits[..] {
+                [] => Boolean::Constant(false),
+                [bit] => bit.clone(),
+                _ => parity(cs.namespace(|| format!("parity [{}]", lane * 8 + j)), &bits)?,
+            };
+            digest.push(bit);
+        }
+    }
+
+    Ok(digest)
+}
+
+/// Parity of `bits`, see [`xor_fold_lc`].
+fn parity<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+    mut cs: CS,
+    bits: &[&Boolean],
+) -> std::result::Result<Boolean, SynthesisError> {
+    let sum = bits
+        .iter()
+        .map(|b| b.get_value().map(u64::from))
+        .sum::<Option<u64>>();
+
+    let parity = AllocatedBit::alloc(cs.namespace(|| "parity"), sum.map(|s| s & 1 == 1))?;
+
+    let quotient_bits = usize::BITS - (bits.len() / 2).leading_zeros();
+    let quotient = (0..quotient_bits)
+        .map(|i| {
+            AllocatedBit::alloc(
+                cs.namespace(|| format!("quotient bit {}", i)),
+                sum.map(|s| (s >> (i + 1)) & 1 == 1),
+            )
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut lhs = LinearCombination::zero();
+    for bit in bits {
+        lhs = lhs + &bit.lc(CS::one(), Scalar::one());
+    }
+
+    let mut rhs = LinearCombination::zero() + parity.get_variable();
+    let mut coeff = Scalar::one();
+    for bit in &quotient {
+        coeff = coeff.double();
+        rhs = rhs + (coeff, bit.get_variable());
+    }
+
+    cs.enforce(
+        || "sum decomposition",
+        |_| lhs,
+        |lc| lc + CS::one(),
+        |_| rhs,
+    );
+
+    Ok(Boolean::from(parity))
+}
+
+/// Masks out every byte of `data` past the first `length` bytes, so they don't
+/// affect whatever is computed over the result. `length` is a private witness
+/// (`None` when we don't have it) in the range `0..=data.len() / 8`.
+///
+/// The length is witnessed as one `active` bit per byte. They are constrained
+/// to form a prefix of ones followed by zeros, and every bit of a byte is
+/// ANDed with the `active` bit of that byte.
+pub fn mask_prefix<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+    mut cs: CS,
+    data: &[Boolean],
+    length: Option<usize>,
+) -> Result<Vec<Boolean>> {
+    let bytes = data.chunks(8).len();
+    match length {
+        Some(length) if length > bytes => {
+            return Err(Error::InvalidLength {
+                what: "bytes",
+                min: 0,
+                max: bytes,
+                got: length,
+            })
+        }
+        _ => {}
+    }
+
+    let mut masked = Vec::with_capacity(data.len());
+    let mut prev_active: Option<AllocatedBit> = None;
+    for (i, chunk) in data.chunks(8).enumerate() {
+        let mut cs = cs.namespace(|| format!("byte {}", i));
+        let active = AllocatedBit::alloc(cs.namespace(|| "active"), length.map(|l| i < l))?;
+
+        // active[i] * (1 - active[i - 1]) = 0, i.e. a byte can only be active
+        // if the previous one is.
+        if let Some(prev) = prev_active {
+            cs.enforce(
+                || "prefix",
+                |lc| lc + active.get_variable(),
+                |lc| lc + CS::one() - prev.get_variable(),
+                |lc| lc,
+            );
+        }
+
+        let is_active = Boolean::from(active.clone());
+        for (j, bit) in chunk.iter().enumerate() {
+            masked.push(Boolean::and(
+                cs.namespace(|| format!("mask bit {}", j)),
+                bit,
+                &is_active,
+            )?);
+        }
+
+        prev_active = Some(active);
+    }
+
+    Ok(masked)
+}
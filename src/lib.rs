@@ -0,0 +1,61 @@
+//! A simple circuit, that proves knowledge of a string used to compute a
+//! XOR-hash over it (XOR all bytes with each other). The length of the string
+//! is chosen at setup, see [`CircuitConfig`]: either every preimage has
+//! exactly that length, or it is the maximum and the actual length stays
+//! private. The hash is a single byte by default, or a wider digest where the
+//! input is folded into several byte lanes. The fold itself has two in-circuit
+//! implementations with different constraint counts, see [`XorGadget`].
+//!
+//! The crate exposes the circuit itself ([`MyCircuit`]), the gadget it is built
+//! from ([`xor_sum`]) and the native reference implementation of the hash
+//! ([`native_xor_sum`]), so that the same definitions can be shared between the
+//! trusted setup, provers and verifiers.
+//!
+//! Fallible functions return the crate's [`Result`], so bad inputs are
+//! reported as an [`Error`] instead of a panic.
+//!
+//! # Features
+//!
+//! - `prover`: the trusted setup, alone or as a multi-party ceremony, and
+//!   proving, with `rand` and bellman's multi-threaded prover.
+//! - `verifier`: verification of proof bundles, one at a time or in batches.
+//! - `solidity`: Solidity verifier contracts and their calldata.
+//! - `cli`: the command line interface, which needs all of the above.
+//!
+//! Everything else, like the circuit definition and the file formats, is
+//! always available. `cli` is on by default; a verifier node builds with
+//! `default-features = false, features = ["verifier"]`, which leaves out the
+//! prover's dependencies.
+
+pub mod batch;
+mod circuit;
+pub mod cs;
+mod decimal;
+mod error;
+mod gadget;
+#[cfg(feature = "prover")]
+pub mod mpc;
+mod native;
+pub mod params;
+pub mod proof;
+pub mod r1cs;
+#[cfg(feature = "insecure-seeded-rng")]
+pub mod seeded;
+pub mod snarkjs;
+#[cfg(feature = "solidity")]
+pub mod solidity;
+
+pub use circuit::{CircuitConfig, MyCircuit, XorGadget};
+pub use error::{Error, Result};
+pub use gadget::{mask_prefix, xor_fold, xor_fold_lc, xor_sum};
+pub use native::{native_xor_fold, native_xor_fold_prefix, native_xor_sum, native_xor_sum_prefix};
+
+/// Default size of the preimage in bytes.
+pub const INPUT_SIZE: usize = 32;
+
+/// Largest preimage size of a configuration in bytes, which bounds the size of
+/// the circuit synthesized for a configuration read from a file.
+pub const MAX_INPUT_SIZE: usize = 1 << 16;
+
+/// Largest digest size of a configuration in bytes.
+pub const MAX_OUTPUT_SIZE: usize = 1 << 12;
@@ -1,108 +1,1035 @@
-//! This binary demonstrates simple circuit, that proves knowledge of a 32-byte
-//! string used to compute a XOR-hash over it (XOR all bytes with each other).
+//! Command line interface to the xor-sum circuit. The trusted setup, proving and
+//! verification are separate subcommands which exchange parameters, proofs and
+//! public inputs through files, so each of them can run on a different machine.
 
-use bellman::{
-    gadgets::{
-        boolean::{AllocatedBit, Boolean},
-        multipack,
-    },
-    groth16, Circuit, ConstraintSystem, SynthesisError,
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    thread,
+};
+
+use bellman::groth16::{self, Parameters, VerifyingKey};
+use bellman_test::{
+    batch::{self, BatchVerifier},
+    cs,
+    mpc::{MpcParameters, PowersOfTau},
+    native_xor_fold, params,
+    proof::ProofBundle,
+    r1cs,
+    r1cs::R1csCircuit,
+    snarkjs, solidity, CircuitConfig, Error, MyCircuit, XorGadget, INPUT_SIZE,
 };
-use bls12_381::Bls12;
-use ff::PrimeField;
+use bls12_381::{Bls12, Scalar};
+use clap::{Args, Parser, Subcommand};
 use log::LevelFilter;
-use rand::rngs::OsRng;
+use rand::{rngs::OsRng, RngCore};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(about = "Groth16 proofs of knowledge of a xor-sum preimage")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the trusted setup and write the parameters and the verifying key.
+    Setup {
+        /// Where to write the full proving parameters.
+        #[arg(long, default_value = "params.bin")]
+        params: PathBuf,
+        /// Where to write the verifying key.
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+        #[command(flatten)]
+        config: ConfigArgs,
+        /// Overwrite existing files. Proofs made with the old parameters will
+        /// no longer verify.
+        #[arg(long)]
+        force: bool,
+        #[command(flatten)]
+        rng: RngArgs,
+    },
+    /// Run a step of the multi-party setup ceremony, instead of `setup`.
+    #[command(subcommand)]
+    Ceremony(CeremonyCommand),
+    /// Prove knowledge of a preimage, writing a proof bundle.
+    Prove {
+        /// Proving parameters produced by `setup`.
+        #[arg(long, default_value = "params.bin")]
+        params: PathBuf,
+        /// The preimage as a string.
+        #[arg(
+            long,
+            conflicts_with = "preimage_hex",
+            required_unless_present = "preimage_hex"
+        )]
+        preimage: Option<String>,
+        /// The preimage as a hex string.
+        #[arg(long)]
+        preimage_hex: Option<String>,
+        /// Verifying key the proof is made for, to fingerprint it in the bundle.
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+        /// Where to write the proof bundle.
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+        /// Write the bundle as JSON instead of binary.
+        #[arg(long)]
+        json: bool,
+        /// Map the parameters into memory and read them lazily, instead of
+        /// loading them all up front.
+        #[arg(long)]
+        mmap: bool,
+        #[command(flatten)]
+        rng: RngArgs,
+    },
+    /// Prove knowledge of many preimages in parallel, writing one proof
+    /// bundle per preimage.
+    ProveBatch {
+        /// Proving parameters produced by `setup`.
+        #[arg(long, default_value = "params.bin")]
+        params: PathBuf,
+        /// Verifying key the proofs are made for.
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+        /// File with one preimage per line.
+        #[arg(long)]
+        preimages: PathBuf,
+        /// Preimages are hex strings.
+        #[arg(long)]
+        hex: bool,
+        /// Directory to write the bundles to, as `proof-<line>.bin`, with
+        /// lines counted from 1 as in error messages.
+        #[arg(long, default_value = ".")]
+        out_dir: PathBuf,
+        /// Number of proofs computed at once, by default the number of CPUs.
+        #[arg(long)]
+        threads: Option<usize>,
+        /// Write the bundles as JSON instead of binary.
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        rng: RngArgs,
+    },
+    /// Verify a proof bundle against a verifying key.
+    Verify {
+        /// Verifying key produced by `setup`.
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+        /// Proof bundle produced by `prove`, binary or JSON.
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+        /// Only accept the proof if its public hash is this hex value.
+        #[arg(long)]
+        hash: Option<String>,
+    },
+    /// Verify many proof bundles against a verifying key at once.
+    VerifyBatch {
+        /// Verifying key produced by `setup`.
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+        /// Proof bundles produced by `prove`, binary or JSON.
+        #[arg(required = true)]
+        proofs: Vec<PathBuf>,
+    },
+    /// Look for under-constrained variables in the circuit.
+    Audit {
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+    /// Report the number of constraints and variables, per namespace.
+    Stats {
+        #[command(flatten)]
+        config: ConfigArgs,
+        /// Group namespaces by this many leading path components.
+        #[arg(long, default_value_t = 1)]
+        depth: usize,
+        /// Print the report as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export the constraint system in the iden3 `.r1cs` format, and
+    /// optionally its witness for a preimage in the `.wtns` format.
+    Export {
+        #[command(flatten)]
+        config: ConfigArgs,
+        /// Where to write the constraint system.
+        #[arg(long, default_value = "circuit.r1cs")]
+        r1cs: PathBuf,
+        /// Where to write the constraint system as JSON, if anywhere.
+        #[arg(long)]
+        json: Option<PathBuf>,
+        /// Where to write the witness, requires a preimage.
+        #[arg(long, requires = "preimage_source")]
+        wtns: Option<PathBuf>,
+        /// The preimage as a string.
+        #[arg(long, group = "preimage_source")]
+        preimage: Option<String>,
+        /// The preimage as a hex string.
+        #[arg(long, group = "preimage_source")]
+        preimage_hex: Option<String>,
+    },
+    /// Run the setup on a circuit in the iden3 `.r1cs` format, such as one
+    /// compiled by circom, then prove and verify its witness if given one.
+    Import {
+        /// The constraint system.
+        #[arg(long, default_value = "circuit.r1cs")]
+        r1cs: PathBuf,
+        /// The witness in the `.wtns` format.
+        #[arg(long)]
+        wtns: Option<PathBuf>,
+    },
+    /// Convert a verifying key and a proof bundle to the JSON files of
+    /// snarkjs: `verification_key.json`, `proof.json` and `public.json`.
+    Snarkjs {
+        /// Verifying key produced by `setup`.
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+        /// Proof bundle produced by `prove`, binary or JSON.
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+        /// Directory to write the files to.
+        #[arg(long, default_value = ".")]
+        out_dir: PathBuf,
+    },
+    /// Generate a Solidity contract that verifies proofs on-chain.
+    Solidity {
+        /// Verifying key produced by `setup`.
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+        /// Where to write the contract.
+        #[arg(long, default_value = "Verifier.sol")]
+        contract: PathBuf,
+    },
+    /// Print the calldata that verifies a proof bundle with the Solidity
+    /// contract, hex-encoded.
+    Calldata {
+        /// Verifying key produced by `setup`.
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+        /// Proof bundle produced by `prove`, binary or JSON.
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+    },
+    /// Compare the number of constraints of both xor gadgets.
+    Constraints {
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+}
 
-const INPUT_SIZE: usize = 32;
+/// Steps of the multi-party setup. Phase 1 computes powers of tau for every
+/// circuit up to some size, phase 2 the parameters of one configuration from
+/// them. Participants of each phase contribute in turn, each on the file of the
+/// previous one. The parameters are secure if at least one participant of each
+/// phase was honest and forgot their randomness.
+#[derive(Subcommand)]
+enum CeremonyCommand {
+    /// Start phase 1, for circuits of up to 2^power constraints.
+    NewPowers {
+        /// Base 2 logarithm of the maximum number of constraints.
+        #[arg(long)]
+        power: u32,
+        /// Where to write the powers of tau.
+        #[arg(long, default_value = "powers.bin")]
+        out: PathBuf,
+    },
+    /// Contribute to phase 1, and print the new hash of the transcript.
+    ContributePowers {
+        /// Powers of tau from the previous participant.
+        input: PathBuf,
+        /// Where to write the powers of tau with this contribution.
+        output: PathBuf,
+        #[command(flatten)]
+        rng: RngArgs,
+    },
+    /// Verify phase 1 and print its transcript.
+    VerifyPowers {
+        /// Powers of tau from the last participant.
+        #[arg(default_value = "powers.bin")]
+        powers: PathBuf,
+    },
+    /// Start phase 2 for a configuration, from the result of phase 1.
+    NewParams {
+        /// Powers of tau from the last participant of phase 1.
+        #[arg(long, default_value = "powers.bin")]
+        powers: PathBuf,
+        #[command(flatten)]
+        config: ConfigArgs,
+        /// Where to write the parameters of the ceremony.
+        #[arg(long, default_value = "ceremony.bin")]
+        out: PathBuf,
+    },
+    /// Contribute to phase 2, and print the new hash of the transcript.
+    ContributeParams {
+        /// Parameters from the previous participant.
+        input: PathBuf,
+        /// Where to write the parameters with this contribution.
+        output: PathBuf,
+        #[command(flatten)]
+        rng: RngArgs,
+    },
+    /// Verify both phases and print their transcripts, then write the
+    /// parameters and the verifying key, like `setup`.
+    Finish {
+        /// Powers of tau from the last participant of phase 1.
+        #[arg(long, default_value = "powers.bin")]
+        powers: PathBuf,
+        /// Parameters from the last participant of phase 2.
+        #[arg(long, default_value = "ceremony.bin")]
+        ceremony: PathBuf,
+        /// Where to write the full proving parameters.
+        #[arg(long, default_value = "params.bin")]
+        params: PathBuf,
+        /// Where to write the verifying key.
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+        /// Overwrite existing files. Proofs made with the old parameters will
+        /// no longer verify.
+        #[arg(long)]
+        force: bool,
+    },
+}
 
-fn native_xor_sum(data: &[u8]) -> u8 {
-    assert_eq!(data.len(), INPUT_SIZE);
-    data.iter().fold(0, |prev, next| prev ^ next)
+/// Source of randomness for the setup and proving.
+#[derive(Args)]
+struct RngArgs {
+    /// INSECURE: seed the RNG with this 32-byte hex string instead of using
+    /// the OS RNG, for reproducible runs. Anyone who knows the seed can forge
+    /// proofs or learn about the preimage.
+    #[cfg(feature = "insecure-seeded-rng")]
+    #[arg(long)]
+    insecure_seed: Option<String>,
 }
 
-/// xor-sum gadget. Data should have 32 bytes or 256 boolean elements
-fn xor_sum<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
-    mut cs: CS,
-    data: &[Boolean],
-) -> Result<Vec<Boolean>, SynthesisError> {
-    assert_eq!(data.len(), INPUT_SIZE * 8);
+/// One RNG per proof of a batch, see `batch::prove_batch`.
+type BatchRngs = Box<dyn Fn(usize) -> Box<dyn RngCore> + Sync>;
+
+impl RngArgs {
+    #[cfg(feature = "insecure-seeded-rng")]
+    fn seed(&self) -> Result<Option<[u8; bellman_test::seeded::SEED_SIZE]>> {
+        match &self.insecure_seed {
+            Some(seed) => {
+                log::warn!("USING A SEEDED RNG, THE OUTPUT IS INSECURE AND ONLY FOR TESTING");
+                Ok(Some(bellman_test::seeded::insecure_seed_from_hex(seed)?))
+            }
+            None => Ok(None),
+        }
+    }
 
-    let mut byte = vec![Boolean::Constant(false); 8];
-    for (i, chunk) in data.chunks(8).enumerate() {
-        for (a, b) in byte.iter_mut().zip(chunk.iter()) {
-            *a = Boolean::xor(cs.namespace(|| format!("xor [{}]", i)), a, b)?;
+    fn rng(&self) -> Result<Box<dyn RngCore>> {
+        #[cfg(feature = "insecure-seeded-rng")]
+        {
+            if let Some(seed) = self.seed()? {
+                return Ok(Box::new(bellman_test::seeded::insecure_rng(seed)));
+            }
         }
+        Ok(Box::new(OsRng))
     }
 
-    Ok(byte)
+    /// With a seed, proof `i` uses stream `i` of the seeded RNG, so the
+    /// proofs don't depend on the number of threads.
+    fn batch_rngs(&self) -> Result<BatchRngs> {
+        #[cfg(feature = "insecure-seeded-rng")]
+        {
+            if let Some(seed) = self.seed()? {
+                return Ok(Box::new(move |i| {
+                    Box::new(bellman_test::seeded::insecure_batch_rng(seed, i))
+                }));
+            }
+        }
+        Ok(Box::new(|_| Box::new(OsRng)))
+    }
 }
 
-struct MyCircuit {
-    /// The input to xor-sum we are proving that we know. Set to `None` when we
-    /// are verifying a proof (and do not have the witness data).
-    preimage: Option<[u8; INPUT_SIZE]>,
+/// Circuit configuration, fixed at setup.
+#[derive(Args)]
+struct ConfigArgs {
+    /// Size of the preimage in bytes, or the maximum size with
+    /// `--variable-length`.
+    #[arg(long, default_value_t = INPUT_SIZE)]
+    input_size: usize,
+    /// Accept preimages of any length up to `--input-size`, keeping the
+    /// actual length private.
+    #[arg(long)]
+    variable_length: bool,
+    /// Size of the digest in bytes.
+    #[arg(long, default_value_t = 1)]
+    output_size: usize,
+    /// In-circuit implementation of the xor: `boolean` or `linear`.
+    #[arg(long, default_value_t = XorGadget::Boolean)]
+    gadget: XorGadget,
 }
 
-impl<Scalar: PrimeField> Circuit<Scalar> for MyCircuit {
-    fn synthesize<CS: ConstraintSystem<Scalar>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
-        // Compute the values for the bits of the preimage. If we are verifying a proof,
-        // we still need to create the same constraints, so we return an equivalent-size
-        // Vec of None (indicating that the value of each bit is unknown).
-        let bit_values = if let Some(preimage) = self.preimage {
-            preimage
-                .iter()
-                .map(|byte| (0..8).map(move |i| (byte >> i) & 1u8 == 1u8))
-                .flatten()
-                .map(|b| Some(b))
-                .collect()
-        } else {
-            vec![None; INPUT_SIZE * 8]
+impl ConfigArgs {
+    fn to_config(&self) -> Result<CircuitConfig> {
+        let config = CircuitConfig {
+            input_size: self.input_size,
+            variable_length: self.variable_length,
+            output_size: self.output_size,
+            gadget: self.gadget,
         };
+        config.check()?;
+        Ok(config)
+    }
+}
 
-        let preimage_bits = bit_values
-            .into_iter()
-            .enumerate()
-            .map(|(i, b)| AllocatedBit::alloc(cs.namespace(|| format!("preimage bit {}", i)), b))
-            .map(|b| b.map(Boolean::from))
-            .collect::<Result<Vec<_>, _>>()?;
+fn main() {
+    simple_logging::log_to_stderr(LevelFilter::Debug);
 
-        let hash = xor_sum(cs.namespace(|| "xor_sum(preimage)"), &preimage_bits)?;
+    let result = match Cli::parse().command {
+        Command::Setup {
+            params,
+            vk,
+            config,
+            force,
+            rng,
+        } => config
+            .to_config()
+            .and_then(|config| setup(&params, &vk, config, force, rng.rng()?)),
+        Command::Ceremony(command) => ceremony(command),
+        Command::Prove {
+            params,
+            preimage,
+            preimage_hex,
+            vk,
+            proof,
+            json,
+            mmap,
+            rng,
+        } => parse_preimage(preimage, preimage_hex)
+            .and_then(|preimage| prove(&params, &vk, preimage, &proof, json, mmap, rng.rng()?)),
+        Command::ProveBatch {
+            params,
+            vk,
+            preimages,
+            hex,
+            out_dir,
+            threads,
+            json,
+            rng,
+        } => read_preimages(&preimages, hex).and_then(|preimages| {
+            prove_batch(
+                &params,
+                &vk,
+                preimages,
+                &out_dir,
+                threads,
+                json,
+                rng.batch_rngs()?,
+            )
+        }),
+        Command::Verify { vk, proof, hash } => verify(&vk, &proof, hash.as_deref()),
+        Command::VerifyBatch { vk, proofs } => verify_batch(&vk, &proofs),
+        Command::Audit { config } => config.to_config().and_then(audit),
+        Command::Stats {
+            config,
+            depth,
+            json,
+        } => config
+            .to_config()
+            .and_then(|config| stats(config, depth, json)),
+        Command::Export {
+            config,
+            r1cs,
+            json,
+            wtns,
+            preimage,
+            preimage_hex,
+        } => config.to_config().and_then(|config| {
+            let preimage = match wtns {
+                Some(ref wtns) => Some((wtns.as_path(), parse_preimage(preimage, preimage_hex)?)),
+                None => None,
+            };
+            export(config, &r1cs, json.as_deref(), preimage)
+        }),
+        Command::Import { r1cs, wtns } => import(&r1cs, wtns.as_deref()),
+        Command::Snarkjs { vk, proof, out_dir } => to_snarkjs(&vk, &proof, &out_dir),
+        Command::Solidity { vk, contract } => generate_contract(&vk, &contract),
+        Command::Calldata { vk, proof } => calldata(&vk, &proof),
+        Command::Constraints { config } => config.to_config().and_then(constraints),
+    };
 
-        // ??
-        multipack::pack_into_inputs(cs.namespace(|| "pack hash"), &hash)
+    if let Err(e) = result {
+        log::error!("{}", e);
+        std::process::exit(1);
     }
 }
 
-fn main() {
-    simple_logging::log_to_stderr(LevelFilter::Debug);
-    log::info!("Generating params...");
-    let params = {
-        let c = MyCircuit { preimage: None };
-        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).unwrap()
+fn setup(
+    params_path: &Path,
+    vk_path: &Path,
+    config: CircuitConfig,
+    force: bool,
+    mut rng: Box<dyn RngCore>,
+) -> Result<()> {
+    check_overwrite(&[params_path, vk_path], force)?;
+
+    log::info!(
+        "Generating params for {}-byte preimages...",
+        config.input_size
+    );
+    let params = params::generate_parameters(&config, &mut rng)?;
+
+    log::info!("Writing params to {}...", params_path.display());
+    params::save_parameters(params_path, &config, &params)?;
+    log::info!("Writing verifying key to {}...", vk_path.display());
+    params::save_verifying_key(vk_path, &config, &params.vk)?;
+    Ok(())
+}
+
+fn check_overwrite(paths: &[&Path], force: bool) -> Result<()> {
+    for path in paths {
+        if !force && path.exists() {
+            return Err(format!(
+                "{} already exists, pass --force to overwrite it",
+                path.display()
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+fn ceremony(command: CeremonyCommand) -> Result<()> {
+    match command {
+        CeremonyCommand::NewPowers { power, out } => {
+            log::info!("Starting powers of tau of size 2^{}...", power);
+            write_powers(&out, &PowersOfTau::new(power)?)
+        }
+        CeremonyCommand::ContributePowers { input, output, rng } => {
+            let mut powers = read_powers(&input)?;
+            log::info!("Contributing to the powers of tau...");
+            let hash = powers.contribute(&mut rng.rng()?);
+            write_powers(&output, &powers)?;
+            log::info!("Contribution hash, check that it is in the transcript:");
+            println!("{}", hex::encode(hash));
+            Ok(())
+        }
+        CeremonyCommand::VerifyPowers { powers } => {
+            let powers = read_powers(&powers)?;
+            log::info!("Verifying the powers of tau...");
+            print_transcript(1, &powers.verify(&mut OsRng)?);
+            Ok(())
+        }
+        CeremonyCommand::NewParams {
+            powers,
+            config,
+            out,
+        } => {
+            let config = config.to_config()?;
+            let powers = read_powers(&powers)?;
+            log::info!(
+                "Computing the initial params for {}-byte preimages...",
+                config.input_size
+            );
+            write_ceremony(&out, &MpcParameters::new(&config, &powers)?)
+        }
+        CeremonyCommand::ContributeParams { input, output, rng } => {
+            let mut ceremony = read_ceremony(&input)?;
+            log::info!("Contributing to the params...");
+            let hash = ceremony.contribute(&mut rng.rng()?);
+            write_ceremony(&output, &ceremony)?;
+            log::info!("Contribution hash, check that it is in the transcript:");
+            println!("{}", hex::encode(hash));
+            Ok(())
+        }
+        CeremonyCommand::Finish {
+            powers,
+            ceremony,
+            params,
+            vk,
+            force,
+        } => finish_ceremony(&powers, &ceremony, &params, &vk, force),
+    }
+}
+
+fn read_powers(path: &Path) -> Result<PowersOfTau> {
+    log::info!("Reading powers of tau from {}...", path.display());
+    Ok(PowersOfTau::read(BufReader::new(File::open(path)?))?)
+}
+
+fn write_powers(path: &Path, powers: &PowersOfTau) -> Result<()> {
+    log::info!("Writing powers of tau to {}...", path.display());
+    let mut writer = BufWriter::new(File::create(path)?);
+    powers.write(&mut writer)?;
+    Ok(writer.flush()?)
+}
+
+fn read_ceremony(path: &Path) -> Result<MpcParameters> {
+    log::info!("Reading ceremony params from {}...", path.display());
+    Ok(MpcParameters::read(BufReader::new(File::open(path)?))?)
+}
+
+fn write_ceremony(path: &Path, ceremony: &MpcParameters) -> Result<()> {
+    log::info!("Writing ceremony params to {}...", path.display());
+    let mut writer = BufWriter::new(File::create(path)?);
+    ceremony.write(&mut writer)?;
+    Ok(writer.flush()?)
+}
+
+fn print_transcript(phase: usize, transcript: &[[u8; 32]]) {
+    for (i, hash) in transcript.iter().enumerate() {
+        println!(
+            "phase {} contribution {}: {}",
+            phase,
+            i + 1,
+            hex::encode(hash)
+        );
+    }
+}
+
+fn finish_ceremony(
+    powers_path: &Path,
+    ceremony_path: &Path,
+    params_path: &Path,
+    vk_path: &Path,
+    force: bool,
+) -> Result<()> {
+    check_overwrite(&[params_path, vk_path], force)?;
+
+    let powers = read_powers(powers_path)?;
+    log::info!("Verifying the powers of tau...");
+    let phase1 = powers.verify(&mut OsRng)?;
+    print_transcript(1, &phase1);
+    let ceremony = read_ceremony(ceremony_path)?;
+    log::info!("Verifying the params...");
+    let phase2 = ceremony.verify(&powers, &mut OsRng)?;
+    print_transcript(2, &phase2);
+    for (phase, transcript) in [(1, &phase1), (2, &phase2)] {
+        if transcript.is_empty() {
+            return Err(format!(
+                "phase {} has no contributions, anyone can forge proofs",
+                phase
+            )
+            .into());
+        }
+    }
+
+    let config = ceremony.config();
+    log::info!("Writing params to {}...", params_path.display());
+    params::save_parameters(params_path, config, ceremony.params())?;
+    log::info!("Writing verifying key to {}...", vk_path.display());
+    params::save_verifying_key(vk_path, config, &ceremony.params().vk)?;
+    Ok(())
+}
+
+/// Loads the proving parameters and checks that `vk_path` holds their
+/// verifying key.
+fn load_proving_keys(
+    params_path: &Path,
+    vk_path: &Path,
+) -> Result<(CircuitConfig, Parameters<Bls12>, VerifyingKey<Bls12>)> {
+    log::info!("Reading params from {}...", params_path.display());
+    let (config, params) = params::load_parameters(params_path, false)?;
+    let vk = check_verifying_key(vk_path, &params.vk, params_path)?;
+    Ok((config, params, vk))
+}
+
+/// Loads the verifying key at `vk_path` and checks that it is `expected`, the
+/// key of the parameters at `params_path`.
+fn check_verifying_key(
+    vk_path: &Path,
+    expected: &VerifyingKey<Bls12>,
+    params_path: &Path,
+) -> Result<VerifyingKey<Bls12>> {
+    log::info!("Reading verifying key from {}...", vk_path.display());
+    let (_, vk) = params::load_verifying_key(vk_path)?;
+    if vk != *expected {
+        return Err(format!(
+            "{} doesn't belong to {}",
+            vk_path.display(),
+            params_path.display()
+        )
+        .into());
+    }
+    Ok(vk)
+}
+
+fn write_bundle(bundle: &ProofBundle, proof_path: &Path, json: bool) -> Result<()> {
+    if json {
+        fs::write(proof_path, bundle.to_json() + "\n")?;
+    } else {
+        let mut writer = BufWriter::new(File::create(proof_path)?);
+        bundle.write(&mut writer)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+fn prove(
+    params_path: &Path,
+    vk_path: &Path,
+    preimage: Vec<u8>,
+    proof_path: &Path,
+    json: bool,
+    mmap: bool,
+    mut rng: Box<dyn RngCore>,
+) -> Result<()> {
+    let bundle = if mmap {
+        log::info!("Mapping params from {}...", params_path.display());
+        let (config, params) = params::map_parameters(params_path, false)?;
+        check_verifying_key(vk_path, params.vk(), params_path)?;
+
+        log::info!("Generating proof...");
+        ProofBundle::create_with_source(&params, params.vk(), &config, preimage, &mut rng)?
+    } else {
+        let (config, params, _) = load_proving_keys(params_path, vk_path)?;
+
+        log::info!("Generating proof...");
+        ProofBundle::create(&params, &config, preimage, &mut rng)?
+    };
+
+    log::info!("Writing proof bundle to {}...", proof_path.display());
+    write_bundle(&bundle, proof_path, json)
+}
+
+fn prove_batch(
+    params_path: &Path,
+    vk_path: &Path,
+    preimages: Vec<Vec<u8>>,
+    out_dir: &Path,
+    threads: Option<usize>,
+    json: bool,
+    rngs: BatchRngs,
+) -> Result<()> {
+    let (config, params, vk) = load_proving_keys(params_path, vk_path)?;
+    for (i, preimage) in preimages.iter().enumerate() {
+        config
+            .check_preimage(preimage)
+            .map_err(|e| format!("line {}: {}", i + 1, e))?;
+    }
+
+    let threads = match threads {
+        Some(0) => return Err("--threads must be at least 1".into()),
+        Some(threads) => threads,
+        None => thread::available_parallelism().map_or(1, usize::from),
     };
+    let extension = if json { "json" } else { "bin" };
+    let proof_path = |i: usize| out_dir.join(format!("proof-{}.{}", i + 1, extension));
+
+    log::info!(
+        "Generating {} proofs on {} threads...",
+        preimages.len(),
+        threads
+    );
+    let mut failed = 0;
+    batch::prove_batch(&params, config, &preimages, threads, rngs, |i, result| {
+        let written = match result {
+            Ok(proof) => native_xor_fold(&preimages[i], config.output_size)
+                .map_err(Into::into)
+                .and_then(|hash| {
+                    write_bundle(
+                        &ProofBundle::new(proof.clone(), hash, &vk),
+                        &proof_path(i),
+                        json,
+                    )
+                }),
+            Err(e) => Err(format!("proving failed: {}", e).into()),
+        };
+        match written {
+            Ok(()) => log::info!(
+                "Line {}: proof written to {}",
+                i + 1,
+                proof_path(i).display()
+            ),
+            Err(e) => {
+                log::error!("Line {}: {}", i + 1, e);
+                failed += 1;
+            }
+        }
+    });
+
+    if failed > 0 {
+        return Err(format!("{} of {} proofs failed", failed, preimages.len()).into());
+    }
+    Ok(())
+}
+
+fn verify(vk_path: &Path, proof_path: &Path, expected_hash: Option<&str>) -> Result<()> {
+    log::info!("Reading verifying key from {}...", vk_path.display());
+    let (config, vk) = params::load_verifying_key(vk_path)?;
     log::info!("Preparing the verification key...");
-    let pvk = groth16::prepare_verifying_key(&params.vk);
+    let pvk = groth16::prepare_verifying_key(&vk);
+
+    log::info!("Reading proof bundle from {}...", proof_path.display());
+    let bundle = read_bundle(proof_path)?;
+    if !bundle.is_for(&vk) {
+        return Err("proof was made for a different verifying key".into());
+    }
+    if let Some(expected) = expected_hash {
+        if hex::decode(expected)? != bundle.hash {
+            return Err(format!(
+                "proof is for hash {}, expected {}",
+                hex::encode(&bundle.hash),
+                expected
+            )
+            .into());
+        }
+    }
+
+    log::info!("Checking proof...");
+    bundle.verify(&pvk, config.output_size)?;
+    log::info!("Success! Hash: {}", hex::encode(&bundle.hash));
+    Ok(())
+}
+
+fn verify_batch(vk_path: &Path, proof_paths: &[PathBuf]) -> Result<()> {
+    log::info!("Reading verifying key from {}...", vk_path.display());
+    let (config, vk) = params::load_verifying_key(vk_path)?;
+    log::info!("Preparing the verification key...");
+    let verifier = BatchVerifier::new(&vk);
+
+    log::info!("Reading {} proof bundles...", proof_paths.len());
+    let bundles = proof_paths
+        .iter()
+        .map(|path| read_bundle(path))
+        .collect::<Result<Vec<_>>>()?;
+    let (mut checked, mut invalid) = (vec![], vec![]);
+    for (i, bundle) in bundles.iter().enumerate() {
+        let path = proof_paths[i].display();
+        if !bundle.is_for(&vk) {
+            log::error!("{} was made for a different verifying key", path);
+            invalid.push(i);
+        } else if let Err(e) = bundle.public_inputs(config.output_size) {
+            log::error!("{}: {}", path, e);
+            invalid.push(i);
+        } else {
+            checked.push(i);
+        }
+    }
+
+    log::info!("Checking proofs...");
+    let checked_bundles = checked
+        .iter()
+        .map(|&i| bundles[i].clone())
+        .collect::<Vec<_>>();
+    match verifier.verify_bundles(&checked_bundles, config.output_size, &mut OsRng) {
+        Ok(()) => {}
+        Err(Error::InvalidProofs(failed)) => {
+            for &i in &failed {
+                log::error!("{} is invalid", proof_paths[checked[i]].display());
+                invalid.push(checked[i]);
+            }
+        }
+        Err(e) => return Err(e.into()),
+    }
+    invalid.sort_unstable();
+    if !invalid.is_empty() {
+        return Err(format!(
+            "{} of {} proofs failed verification",
+            invalid.len(),
+            bundles.len()
+        )
+        .into());
+    }
+    log::info!("Success! All {} proofs are valid.", bundles.len());
+    Ok(())
+}
+
+fn audit(config: CircuitConfig) -> Result<()> {
+    let report = cs::audit::<Scalar, _>(MyCircuit::new(config))?;
+    print!("{}", report);
+    if report.is_clean() {
+        Ok(())
+    } else {
+        Err("the circuit may be under-constrained".into())
+    }
+}
+
+fn stats(config: CircuitConfig, depth: usize, json: bool) -> Result<()> {
+    let stats = cs::stats::<Scalar, _>(MyCircuit::new(config), depth)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print!("{}", stats);
+    }
+    Ok(())
+}
+
+fn export(
+    config: CircuitConfig,
+    r1cs_path: &Path,
+    json_path: Option<&Path>,
+    witness: Option<(&Path, Vec<u8>)>,
+) -> Result<()> {
+    log::info!("Recording the constraint system...");
+    let (r1cs, _) = cs::record::<Scalar, _>(MyCircuit::new(config))?;
+
+    log::info!("Writing constraint system to {}...", r1cs_path.display());
+    let mut writer = BufWriter::new(File::create(r1cs_path)?);
+    r1cs::write_r1cs(&r1cs, &mut writer)?;
+    writer.flush()?;
+
+    if let Some(json_path) = json_path {
+        log::info!("Writing constraint system to {}...", json_path.display());
+        fs::write(
+            json_path,
+            serde_json::to_string(&r1cs::to_json(&r1cs))? + "\n",
+        )?;
+    }
+
+    if let Some((wtns_path, preimage)) = witness {
+        config.check_preimage(&preimage)?;
+        log::info!("Computing the witness...");
+        let (_, witness) = cs::record::<Scalar, _>(MyCircuit::with_preimage(config, preimage))?;
+        let witness = witness.ok_or("witness is incomplete")?;
+        if let Some(i) = r1cs.which_is_unsatisfied(&witness)? {
+            return Err(format!("witness doesn't satisfy constraint {}", i).into());
+        }
 
-    log::info!("Calculating hash...");
-    let mut preimage = *b"da confusion of da highest orda!";
-    let hash = native_xor_sum(&preimage);
+        log::info!("Writing witness to {}...", wtns_path.display());
+        let mut writer = BufWriter::new(File::create(wtns_path)?);
+        r1cs::write_wtns(&witness, &mut writer)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
 
-    // Let's flip some bytes. It should not change the hash
-    preimage.swap(3, 13);
+fn constraints(config: CircuitConfig) -> Result<()> {
+    println!(
+        "{:<8} {:>12} {:>10} {:>8}",
+        "gadget", "constraints", "aux vars", "inputs"
+    );
+    for gadget in XorGadget::ALL.iter().copied() {
+        let shape = CircuitConfig { gadget, ..config }.shape()?;
+        println!(
+            "{:<8} {:>12} {:>10} {:>8}",
+            gadget,
+            shape.num_constraints(),
+            shape.num_aux(),
+            shape.num_inputs()
+        );
+    }
+    Ok(())
+}
 
-    // Create an instance of our circuit (with the preimage as a witness).
-    let c = MyCircuit {
-        preimage: Some(preimage),
+fn import(r1cs_path: &Path, wtns_path: Option<&Path>) -> Result<()> {
+    log::info!("Reading constraint system from {}...", r1cs_path.display());
+    let r1cs = r1cs::read_r1cs::<Scalar, _>(BufReader::new(File::open(r1cs_path)?))?;
+    log::info!(
+        "{} constraints, {} public inputs, {} private variables",
+        r1cs.constraints.len(),
+        r1cs.num_public,
+        r1cs.num_private
+    );
+    let prover = match wtns_path {
+        Some(wtns_path) => {
+            log::info!("Reading witness from {}...", wtns_path.display());
+            let witness = r1cs::read_wtns(BufReader::new(File::open(wtns_path)?))?;
+            if let Some(i) = r1cs.which_is_unsatisfied(&witness)? {
+                return Err(format!("witness doesn't satisfy constraint {}", i).into());
+            }
+            let public_inputs = witness[1..=r1cs.num_public].to_vec();
+            Some((
+                R1csCircuit::with_witness(r1cs.clone(), witness)?,
+                public_inputs,
+            ))
+        }
+        None => None,
     };
 
-    log::info!("Generating proof...");
-    let proof = groth16::create_random_proof(c, &params, &mut OsRng).unwrap();
+    log::info!("Generating params...");
+    let params = {
+        let c = R1csCircuit::new(r1cs);
+        groth16::generate_random_parameters::<Bls12, _, _>(c, &mut OsRng).map_err(Error::from)?
+    };
+    let (c, public_inputs) = match prover {
+        Some(prover) => prover,
+        None => return Ok(()),
+    };
 
-    log::info!("Packing hash as input...");
-    let hash_bits = multipack::bytes_to_bits_le(&[hash]);
-    let inputs = multipack::compute_multipacking(&hash_bits);
+    log::info!("Generating proof...");
+    let proof = groth16::create_random_proof(c, &params, &mut OsRng).map_err(Error::from)?;
 
     log::info!("Checking proof...");
-    assert!(groth16::verify_proof(&pvk, &proof, &inputs).is_ok());
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+    groth16::verify_proof(&pvk, &proof, &public_inputs).map_err(Error::from)?;
     log::info!("Success!");
+    Ok(())
+}
+
+fn to_snarkjs(vk_path: &Path, proof_path: &Path, out_dir: &Path) -> Result<()> {
+    log::info!("Reading verifying key from {}...", vk_path.display());
+    let (config, vk) = params::load_verifying_key(vk_path)?;
+    log::info!("Reading proof bundle from {}...", proof_path.display());
+    let bundle = read_bundle(proof_path)?;
+    if !bundle.is_for(&vk) {
+        return Err("proof was made for a different verifying key".into());
+    }
+
+    let files = [
+        ("verification_key.json", snarkjs::verifying_key_to_json(&vk)),
+        ("proof.json", snarkjs::proof_to_json(&bundle.proof)),
+        (
+            "public.json",
+            snarkjs::public_inputs_to_json(&bundle.public_inputs(config.output_size)?),
+        ),
+    ];
+    for (name, json) in files.iter() {
+        let path = out_dir.join(name);
+        log::info!("Writing {}...", path.display());
+        fs::write(path, format!("{}\n", json))?;
+    }
+    Ok(())
+}
+
+fn generate_contract(vk_path: &Path, contract_path: &Path) -> Result<()> {
+    log::info!("Reading verifying key from {}...", vk_path.display());
+    let (_, vk) = params::load_verifying_key(vk_path)?;
+    log::info!("Writing contract to {}...", contract_path.display());
+    fs::write(contract_path, solidity::verifier_contract(&vk)?)?;
+    Ok(())
+}
+
+fn calldata(vk_path: &Path, proof_path: &Path) -> Result<()> {
+    log::info!("Reading verifying key from {}...", vk_path.display());
+    let (config, vk) = params::load_verifying_key(vk_path)?;
+    log::info!("Reading proof bundle from {}...", proof_path.display());
+    let bundle = read_bundle(proof_path)?;
+    if !bundle.is_for(&vk) {
+        return Err("proof was made for a different verifying key".into());
+    }
+    let calldata = solidity::calldata(&bundle.proof, &bundle.public_inputs(config.output_size)?);
+    println!("0x{}", hex::encode(calldata));
+    Ok(())
+}
+
+/// Reads a proof bundle in either encoding. JSON bundles are objects, while
+/// binary ones start with a magic, so the first byte tells them apart.
+fn read_bundle(path: &Path) -> Result<ProofBundle> {
+    let data = fs::read(path)?;
+    if data.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{') {
+        Ok(ProofBundle::from_json(std::str::from_utf8(&data)?)?)
+    } else {
+        Ok(ProofBundle::read(&data[..])?)
+    }
+}
+
+/// Reads one preimage per line of `path`.
+fn read_preimages(path: &Path, hex: bool) -> Result<Vec<Vec<u8>>> {
+    log::info!("Reading preimages from {}...", path.display());
+    fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if hex {
+                hex::decode(line).map_err(|e| format!("line {}: {}", i + 1, e).into())
+            } else {
+                Ok(line.as_bytes().to_vec())
+            }
+        })
+        .collect()
+}
+
+fn parse_preimage(preimage: Option<String>, preimage_hex: Option<String>) -> Result<Vec<u8>> {
+    match (preimage, preimage_hex) {
+        (Some(s), _) => Ok(s.into_bytes()),
+        (None, Some(h)) => Ok(hex::decode(h)?),
+        (None, None) => unreachable!("clap requires one of the preimage arguments"),
+    }
 }
@@ -0,0 +1,230 @@
+//! Multi-party computation of the parameters of [`MyCircuit`], so that nobody
+//! knows the toxic waste.
+//!
+//! [`generate_parameters`](crate::params::generate_parameters) samples every
+//! secret of the setup at once, and whoever runs it can forge proofs. The
+//! ceremony here splits the setup in two phases, like the Zcash Sapling one:
+//!
+//! 1. [`PowersOfTau`], independent of the circuit, computes `tau^i`,
+//!    `alpha tau^i` and `beta tau^i` in the exponent for secret `tau`, `alpha`
+//!    and `beta`, up to some maximum number of constraints.
+//! 2. [`MpcParameters`] starts from the parameters of [`MyCircuit`] with a
+//!    configuration, derived from the result of phase 1 with `delta = 1`. It
+//!    fixes the circuit, so phase 1 can be reused for every configuration that
+//!    fits.
+//!
+//! In either phase, participants contribute in turn: each one multiplies the
+//! secrets so far by random ones of their own, forgets those, and appends a
+//! public key proving that they knew them. The result is secure as long as at
+//! least one participant of each phase was honest.
+//!
+//! The public keys form a hash chain: the hashes after each contribution are
+//! the transcript of the ceremony, which a participant checks to contain the
+//! hash printed when they contributed. Anyone can verify the whole chain from
+//! the final files, and should, before trusting the verifying key.
+//!
+//! The proof of knowledge of a secret `x` is a random `s` in G1 with `s x`,
+//! and `r x` where `r` in G2 is hashed from the previous hash of the chain,
+//! `s` and `s x`. Whether two pairs of points are related by the same secret
+//! is checked with pairings.
+//!
+//! [`MyCircuit`]: crate::MyCircuit
+
+use std::io::{Read, Write};
+
+use blake2s_simd::Params as Blake2sParams;
+use bls12_381::{multi_miller_loop, G1Affine, G2Affine, G2Prepared, G2Projective, Gt, Scalar};
+use ff::Field;
+use group::{prime::PrimeCurveAffine, Curve, Group, UncompressedEncoding};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use rand_core::RngCore;
+
+use crate::{Error, Result};
+
+mod phase1;
+mod phase2;
+
+pub use phase1::{PowersOfTau, MAX_POWER};
+pub use phase2::MpcParameters;
+
+/// Hash of the chain after each contribution.
+pub type Transcript = Vec<[u8; 32]>;
+
+/// BLAKE2s digest of the concatenation of `parts`.
+fn hash(parts: &[&[u8]]) -> [u8; 32] {
+    let mut state = Blake2sParams::new()
+        .hash_length(32)
+        .personal(b"XSUM_MPC")
+        .to_state();
+    for part in parts {
+        state.update(part);
+    }
+    let mut digest = [0; 32];
+    digest.copy_from_slice(state.finalize().as_bytes());
+    digest
+}
+
+/// Whether `g1.1 = g1.0 x` and `g2.1 = g2.0 x` for the same `x`, that is
+/// `e(g1.0, g2.1) = e(g1.1, g2.0)`.
+fn same_ratio(g1: (G1Affine, G1Affine), g2: (G2Affine, G2Affine)) -> bool {
+    let (g2_0, g2_1) = (G2Prepared::from(g2.0), G2Prepared::from(g2.1));
+    multi_miller_loop(&[(&g1.0, &g2_1), (&-g1.1, &g2_0)]).final_exponentiation() == Gt::identity()
+}
+
+/// The same random linear combination of `v1` and of `v2`. Every pair of
+/// points of `v1` and `v2` has the same ratio if, and except with negligible
+/// probability only if, the two combinations do.
+fn merge_pairs<G, R>(v1: &[G], v2: &[G], rng: &mut R) -> (G, G)
+where
+    G: PrimeCurveAffine<Scalar = Scalar>,
+    R: RngCore,
+{
+    assert_eq!(v1.len(), v2.len());
+    let mut s1 = G::Curve::identity();
+    let mut s2 = G::Curve::identity();
+    for (&p1, &p2) in v1.iter().zip(v2) {
+        let r = Scalar::random(&mut *rng);
+        s1 += p1 * r;
+        s2 += p2 * r;
+    }
+    (s1.to_affine(), s2.to_affine())
+}
+
+/// Merges the pairs of consecutive points of `v`, which all have the same
+/// ratio if `v` holds successive powers of some `x` in the exponent.
+fn power_pairs<G, R>(v: &[G], rng: &mut R) -> (G, G)
+where
+    G: PrimeCurveAffine<Scalar = Scalar>,
+    R: RngCore,
+{
+    merge_pairs(&v[..v.len() - 1], &v[1..], rng)
+}
+
+/// A random non-zero scalar.
+fn random_secret<R: RngCore>(rng: &mut R) -> Scalar {
+    loop {
+        let x = Scalar::random(&mut *rng);
+        if !x.is_zero() {
+            return x;
+        }
+    }
+}
+
+/// Proof of knowledge of a secret `x`, bound to the chain by hashing its
+/// previous hash into `r`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Knowledge {
+    s: G1Affine,
+    s_x: G1Affine,
+    r_x: G2Affine,
+}
+
+impl Knowledge {
+    fn new<R: RngCore>(x: Scalar, previous: &[u8; 32], tag: &[u8], rng: &mut R) -> Self {
+        let s = loop {
+            let s = G1Affine::from(G1Affine::generator() * random_secret(&mut *rng));
+            if !bool::from(s.is_identity()) {
+                break s;
+            }
+        };
+        let s_x = G1Affine::from(s * x);
+        let r_x = G2Affine::from(Self::r(previous, tag, &s, &s_x) * x);
+        Knowledge { s, s_x, r_x }
+    }
+
+    /// `r`, hashed to G2 by seeding a ChaCha20 RNG with a digest. `tag` tells
+    /// apart the secrets of one contribution.
+    fn r(previous: &[u8; 32], tag: &[u8], s: &G1Affine, s_x: &G1Affine) -> G2Affine {
+        let digest = hash(&[
+            previous,
+            tag,
+            s.to_uncompressed().as_ref(),
+            s_x.to_uncompressed().as_ref(),
+        ]);
+        G2Projective::random(ChaCha20Rng::from_seed(digest)).to_affine()
+    }
+
+    /// Checks the proof, and returns `(r, r x)` to check what the contribution
+    /// did with `x`.
+    fn check(&self, previous: &[u8; 32], tag: &[u8]) -> Option<(G2Affine, G2Affine)> {
+        let r = Self::r(previous, tag, &self.s, &self.s_x);
+        let valid = !bool::from(self.s.is_identity())
+            && !bool::from(self.s_x.is_identity())
+            && same_ratio((self.s, self.s_x), (r, self.r_x));
+        if valid {
+            Some((r, self.r_x))
+        } else {
+            None
+        }
+    }
+
+    fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        write_point(&mut writer, &self.s)?;
+        write_point(&mut writer, &self.s_x)?;
+        write_point(&mut writer, &self.r_x)
+    }
+
+    fn read<R: Read>(mut reader: R) -> Result<Self> {
+        Ok(Knowledge {
+            s: read_point(&mut reader)?,
+            s_x: read_point(&mut reader)?,
+            r_x: read_point(&mut reader)?,
+        })
+    }
+}
+
+/// Checks that `after` is `before` times the secret of `knowledge`, in the
+/// contribution at `index`, counted from 1.
+fn check_step(
+    index: usize,
+    knowledge: &Knowledge,
+    previous: &[u8; 32],
+    tag: &'static str,
+    (before, after): (G1Affine, G1Affine),
+) -> Result<()> {
+    let invalid = |msg| Error::Ceremony(format!("contribution {}: {}", index, msg));
+    let (r, r_x) = knowledge
+        .check(previous, tag.as_bytes())
+        .ok_or_else(|| invalid(format!("invalid proof of knowledge of {}", tag)))?;
+    if bool::from(after.is_identity()) || !same_ratio((before, after), (r, r_x)) {
+        return Err(invalid(format!(
+            "{} doesn't match its proof of knowledge",
+            tag
+        )));
+    }
+    Ok(())
+}
+
+fn write_point<W: Write, G: UncompressedEncoding>(mut writer: W, point: &G) -> Result<()> {
+    Ok(writer.write_all(point.to_uncompressed().as_ref())?)
+}
+
+/// Reads a point, checked to be on the curve and in the right subgroup.
+fn read_point<R: Read, G: UncompressedEncoding>(mut reader: R) -> Result<G> {
+    let mut repr = G::Uncompressed::default();
+    reader.read_exact(repr.as_mut())?;
+    Option::from(G::from_uncompressed(&repr)).ok_or_else(|| Error::decode("invalid point"))
+}
+
+fn write_points<W: Write, G: UncompressedEncoding>(mut writer: W, points: &[G]) -> Result<()> {
+    for point in points {
+        write_point(&mut writer, point)?;
+    }
+    Ok(())
+}
+
+/// Reads `len` points. The length comes from the file, so the vector only
+/// grows as points are actually read.
+fn read_points<R: Read, G: UncompressedEncoding>(mut reader: R, len: usize) -> Result<Vec<G>> {
+    let mut points = vec![];
+    for _ in 0..len {
+        points.push(read_point(&mut reader)?);
+    }
+    Ok(points)
+}
+
+fn read_u32<R: Read>(mut reader: R) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
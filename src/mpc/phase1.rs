@@ -0,0 +1,279 @@
+//! Phase 1 of the ceremony: powers of tau.
+//!
+//! The file format is:
+//!
+//! ```text
+//! magic         : 8 bytes, "XSUMTAU1"
+//! version       : u32, big-endian
+//! power         : u32, big-endian, the size n is 2^power
+//! tau_g1        : 2n - 1 G1 points, tau^i
+//! tau_g2        : n G2 points, tau^i
+//! alpha_tau_g1  : n G1 points, alpha tau^i
+//! beta_tau_g1   : n G1 points, beta tau^i
+//! beta_g2       : G2 point, beta
+//! contributions : u32, big-endian, followed by their public keys
+//! ```
+//!
+//! Points are uncompressed, and checked when read.
+
+use std::io::{Read, Write};
+
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use group::Curve;
+use rand_core::RngCore;
+
+use super::{
+    check_step, hash, power_pairs, random_secret, read_point, read_points, read_u32, same_ratio,
+    write_point, write_points, Knowledge, Transcript,
+};
+use crate::{Error, Result};
+
+const MAGIC: &[u8; 8] = b"XSUMTAU1";
+const VERSION: u32 = 1;
+
+/// Largest supported power of two of the size of the powers of tau.
+pub const MAX_POWER: u32 = 26;
+
+/// The powers of tau accumulated so far, and the public key of every
+/// contribution.
+#[derive(Clone)]
+pub struct PowersOfTau {
+    power: u32,
+    pub(super) tau_g1: Vec<G1Affine>,
+    pub(super) tau_g2: Vec<G2Affine>,
+    pub(super) alpha_tau_g1: Vec<G1Affine>,
+    pub(super) beta_tau_g1: Vec<G1Affine>,
+    pub(super) beta_g2: G2Affine,
+    contributions: Vec<PublicKey>,
+}
+
+/// What a contribution did to `tau`, `alpha` and `beta`: their values in G1
+/// after it, and proofs of knowledge of the secrets it multiplied them by.
+#[derive(Clone, Debug, PartialEq)]
+struct PublicKey {
+    tau_g1: G1Affine,
+    alpha_g1: G1Affine,
+    beta_g1: G1Affine,
+    tau: Knowledge,
+    alpha: Knowledge,
+    beta: Knowledge,
+}
+
+impl PublicKey {
+    fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        write_point(&mut writer, &self.tau_g1)?;
+        write_point(&mut writer, &self.alpha_g1)?;
+        write_point(&mut writer, &self.beta_g1)?;
+        self.tau.write(&mut writer)?;
+        self.alpha.write(&mut writer)?;
+        self.beta.write(&mut writer)
+    }
+
+    fn read<R: Read>(mut reader: R) -> Result<Self> {
+        Ok(PublicKey {
+            tau_g1: read_point(&mut reader)?,
+            alpha_g1: read_point(&mut reader)?,
+            beta_g1: read_point(&mut reader)?,
+            tau: Knowledge::read(&mut reader)?,
+            alpha: Knowledge::read(&mut reader)?,
+            beta: Knowledge::read(&mut reader)?,
+        })
+    }
+}
+
+impl PowersOfTau {
+    /// Starts phase 1 for circuits of up to `2^power` constraints, with every
+    /// secret set to 1.
+    pub fn new(power: u32) -> Result<Self> {
+        if power == 0 || power > MAX_POWER {
+            return Err(Error::InvalidLength {
+                what: "bits of size",
+                min: 1,
+                max: MAX_POWER as usize,
+                got: power as usize,
+            });
+        }
+        let n = 1 << power;
+        Ok(PowersOfTau {
+            power,
+            tau_g1: vec![G1Affine::generator(); 2 * n - 1],
+            tau_g2: vec![G2Affine::generator(); n],
+            alpha_tau_g1: vec![G1Affine::generator(); n],
+            beta_tau_g1: vec![G1Affine::generator(); n],
+            beta_g2: G2Affine::generator(),
+            contributions: vec![],
+        })
+    }
+
+    /// The maximum number of constraints, 2^power.
+    pub fn size(&self) -> usize {
+        self.tau_g2.len()
+    }
+
+    pub fn num_contributions(&self) -> usize {
+        self.contributions.len()
+    }
+
+    /// Hash of the chain before each contribution, then after the last one.
+    fn chain(&self) -> Transcript {
+        let mut chain = vec![hash(&[b"powers of tau", &self.power.to_be_bytes()])];
+        for key in &self.contributions {
+            let mut encoded = vec![];
+            key.write(&mut encoded)
+                .expect("writing to a Vec never fails");
+            chain.push(hash(&[chain.last().unwrap(), &encoded]));
+        }
+        chain
+    }
+
+    /// Hash of the chain after the last contribution, which phase 2 commits
+    /// to.
+    pub fn digest(&self) -> [u8; 32] {
+        *self.chain().last().unwrap()
+    }
+
+    /// Multiplies `tau`, `alpha` and `beta` by secrets drawn from `rng`, and
+    /// returns the new hash of the chain. The secrets are dropped on return.
+    pub fn contribute<R: RngCore>(&mut self, rng: &mut R) -> [u8; 32] {
+        let previous = self.digest();
+        let tau = random_secret(rng);
+        let alpha = random_secret(rng);
+        let beta = random_secret(rng);
+
+        let n = self.size();
+        let mut tau_g1 = Vec::with_capacity(self.tau_g1.len());
+        let mut tau_g2 = Vec::with_capacity(n);
+        let mut alpha_tau_g1 = Vec::with_capacity(n);
+        let mut beta_tau_g1 = Vec::with_capacity(n);
+        let mut power = Scalar::one();
+        for (i, &p) in self.tau_g1.iter().enumerate() {
+            tau_g1.push(p * power);
+            if i < n {
+                tau_g2.push(self.tau_g2[i] * power);
+                alpha_tau_g1.push(self.alpha_tau_g1[i] * (alpha * power));
+                beta_tau_g1.push(self.beta_tau_g1[i] * (beta * power));
+            }
+            power *= tau;
+        }
+        G1Projective::batch_normalize(&tau_g1, &mut self.tau_g1);
+        G2Projective::batch_normalize(&tau_g2, &mut self.tau_g2);
+        G1Projective::batch_normalize(&alpha_tau_g1, &mut self.alpha_tau_g1);
+        G1Projective::batch_normalize(&beta_tau_g1, &mut self.beta_tau_g1);
+        self.beta_g2 = (self.beta_g2 * beta).to_affine();
+
+        self.contributions.push(PublicKey {
+            tau_g1: self.tau_g1[1],
+            alpha_g1: self.alpha_tau_g1[0],
+            beta_g1: self.beta_tau_g1[0],
+            tau: Knowledge::new(tau, &previous, b"tau", rng),
+            alpha: Knowledge::new(alpha, &previous, b"alpha", rng),
+            beta: Knowledge::new(beta, &previous, b"beta", rng),
+        });
+        self.digest()
+    }
+
+    /// Checks every contribution and that the accumulated points are powers
+    /// of the same secrets, and returns the transcript. `rng` only draws the
+    /// coefficients that batch the checks.
+    pub fn verify<R: RngCore>(&self, rng: &mut R) -> Result<Transcript> {
+        let chain = self.chain();
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+
+        let (mut tau, mut alpha, mut beta) = (g1, g1, g1);
+        for (i, (key, previous)) in self.contributions.iter().zip(&chain).enumerate() {
+            check_step(i + 1, &key.tau, previous, "tau", (tau, key.tau_g1))?;
+            check_step(i + 1, &key.alpha, previous, "alpha", (alpha, key.alpha_g1))?;
+            check_step(i + 1, &key.beta, previous, "beta", (beta, key.beta_g1))?;
+            tau = key.tau_g1;
+            alpha = key.alpha_g1;
+            beta = key.beta_g1;
+        }
+
+        let invalid = |msg: &str| Err(Error::Ceremony(msg.to_string()));
+        if self.tau_g1[0] != g1 || self.tau_g2[0] != g2 {
+            return invalid("tau^0 isn't the generator");
+        }
+        if self.tau_g1[1] != tau || self.alpha_tau_g1[0] != alpha || self.beta_tau_g1[0] != beta {
+            return invalid("the powers of tau don't match the last contribution");
+        }
+        let tau_g2 = (self.tau_g2[0], self.tau_g2[1]);
+        if !same_ratio((g1, self.tau_g1[1]), tau_g2)
+            || !same_ratio(power_pairs(&self.tau_g1, rng), tau_g2)
+            || !same_ratio((g1, self.tau_g1[1]), power_pairs(&self.tau_g2, rng))
+        {
+            return invalid("tau_g1 and tau_g2 aren't powers of tau");
+        }
+        if !same_ratio(power_pairs(&self.alpha_tau_g1, rng), tau_g2) {
+            return invalid("alpha_tau_g1 isn't alpha times powers of tau");
+        }
+        if !same_ratio(power_pairs(&self.beta_tau_g1, rng), tau_g2)
+            || !same_ratio((g1, beta), (g2, self.beta_g2))
+        {
+            return invalid("beta_tau_g1 isn't beta times powers of tau");
+        }
+
+        Ok(chain[1..].to_vec())
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_be_bytes())?;
+        writer.write_all(&self.power.to_be_bytes())?;
+        write_points(&mut writer, &self.tau_g1)?;
+        write_points(&mut writer, &self.tau_g2)?;
+        write_points(&mut writer, &self.alpha_tau_g1)?;
+        write_points(&mut writer, &self.beta_tau_g1)?;
+        write_point(&mut writer, &self.beta_g2)?;
+        writer.write_all(&(self.contributions.len() as u32).to_be_bytes())?;
+        for key in &self.contributions {
+            key.write(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the powers of tau. They are only known to be consistent once
+    /// [`verify`](Self::verify) succeeds.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::Decode(format!(
+                "bad magic, expected {:?}",
+                String::from_utf8_lossy(MAGIC)
+            )));
+        }
+        let version = read_u32(&mut reader)?;
+        if version != VERSION {
+            return Err(Error::Decode(format!(
+                "unsupported format version {}, expected {}",
+                version, VERSION
+            )));
+        }
+        let power = read_u32(&mut reader)?;
+        if power == 0 || power > MAX_POWER {
+            return Err(Error::Decode(format!("unsupported power {}", power)));
+        }
+
+        let n = 1 << power;
+        let tau_g1 = read_points(&mut reader, 2 * n - 1)?;
+        let tau_g2 = read_points(&mut reader, n)?;
+        let alpha_tau_g1 = read_points(&mut reader, n)?;
+        let beta_tau_g1 = read_points(&mut reader, n)?;
+        let beta_g2 = read_point(&mut reader)?;
+        let mut contributions = vec![];
+        for _ in 0..read_u32(&mut reader)? {
+            contributions.push(PublicKey::read(&mut reader)?);
+        }
+
+        Ok(PowersOfTau {
+            power,
+            tau_g1,
+            tau_g2,
+            alpha_tau_g1,
+            beta_tau_g1,
+            beta_g2,
+            contributions,
+        })
+    }
+}
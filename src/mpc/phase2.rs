@@ -0,0 +1,362 @@
+//! Phase 2 of the ceremony: `delta`, for one configuration of the circuit.
+//!
+//! The file format is:
+//!
+//! ```text
+//! header        : the header of the parameter files (see `params`), with the
+//!                 magic "XSUMMPC2"
+//! phase 1       : 32 bytes, hash of the chain of the powers of tau it
+//!                 started from
+//! parameters    : bellman's encoding of `Parameters`
+//! contributions : u32, big-endian, followed by their public keys
+//! ```
+//!
+//! Points are uncompressed, and checked when read.
+
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+};
+
+use bellman::{
+    domain::{self, EvaluationDomain},
+    groth16::{Parameters, VerifyingKey},
+    multicore::Worker,
+    SynthesisError,
+};
+use bls12_381::{Bls12, G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use group::{
+    prime::{PrimeCurve, PrimeCurveAffine},
+    Curve,
+};
+use rand_core::RngCore;
+
+use super::{
+    check_step, hash, merge_pairs, random_secret, read_point, read_u32, same_ratio, write_point,
+    Knowledge, PowersOfTau, Transcript,
+};
+use crate::{
+    cs,
+    params::{read_header, write_header},
+    r1cs::Constraint,
+    CircuitConfig, Error, MyCircuit, Result,
+};
+
+const MAGIC: &[u8; 8] = b"XSUMMPC2";
+
+/// Parameters of [`MyCircuit`] for one configuration, and the public key of
+/// every contribution to their `delta`.
+#[derive(Clone)]
+pub struct MpcParameters {
+    config: CircuitConfig,
+    phase1: [u8; 32],
+    /// Hash of the chain before the first contribution.
+    start: [u8; 32],
+    params: Parameters<Bls12>,
+    contributions: Vec<PublicKey>,
+}
+
+/// What a contribution did to `delta`: its value in G1 after it, and a proof
+/// of knowledge of the secret it multiplied it by.
+#[derive(Clone, Debug, PartialEq)]
+struct PublicKey {
+    delta_g1: G1Affine,
+    delta: Knowledge,
+}
+
+impl PublicKey {
+    fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        write_point(&mut writer, &self.delta_g1)?;
+        self.delta.write(&mut writer)
+    }
+
+    fn read<R: Read>(mut reader: R) -> Result<Self> {
+        Ok(PublicKey {
+            delta_g1: read_point(&mut reader)?,
+            delta: Knowledge::read(&mut reader)?,
+        })
+    }
+}
+
+/// A point as a coefficient of bellman's `EvaluationDomain`, which only
+/// provides that for curves with a cofactor.
+#[derive(Clone, Copy)]
+struct Coeff<G>(G);
+
+impl<G: PrimeCurve> domain::Group<G::Scalar> for Coeff<G> {
+    fn group_zero() -> Self {
+        Coeff(G::identity())
+    }
+    fn group_mul_assign(&mut self, by: &G::Scalar) {
+        self.0 *= by;
+    }
+    fn group_add_assign(&mut self, other: &Self) {
+        self.0 += other.0;
+    }
+    fn group_sub_assign(&mut self, other: &Self) {
+        self.0 -= other.0;
+    }
+}
+
+/// The points of `powers`, `x tau^i` in the exponent, in the Lagrange basis:
+/// `x L_i(tau)`.
+fn lagrange<G: PrimeCurve>(powers: &[G::Affine], worker: &Worker) -> Result<Vec<G>> {
+    let points = powers.iter().map(|&p| Coeff(p.to_curve())).collect();
+    let mut domain = EvaluationDomain::from_coeffs(points)?;
+    domain.ifft(worker);
+    Ok(domain.into_coeffs().into_iter().map(|p| p.0).collect())
+}
+
+fn to_affine<G: Curve>(points: &[G]) -> Vec<G::AffineRepr>
+where
+    G::AffineRepr: Clone + Default,
+{
+    let mut affine = vec![G::AffineRepr::default(); points.len()];
+    G::batch_normalize(points, &mut affine);
+    affine
+}
+
+fn scale(points: &[G1Affine], x: Scalar) -> Arc<Vec<G1Affine>> {
+    let scaled = points.iter().map(|&p| p * x).collect::<Vec<_>>();
+    Arc::new(to_affine(&scaled))
+}
+
+fn without_identity<G: PrimeCurveAffine>(points: Vec<G>) -> Arc<Vec<G>> {
+    Arc::new(
+        points
+            .into_iter()
+            .filter(|p| !bool::from(p.is_identity()))
+            .collect(),
+    )
+}
+
+impl MpcParameters {
+    /// Starts phase 2 for [`MyCircuit`] with `config`, from the result of
+    /// phase 1 and with `delta` set to 1. Everyone computes the same
+    /// parameters from the same powers of tau.
+    pub fn new(config: &CircuitConfig, phase1: &PowersOfTau) -> Result<Self> {
+        config.check()?;
+        let (r1cs, _) = cs::record::<Scalar, _>(MyCircuit::new(*config))?;
+        let num_inputs = r1cs.num_public + 1;
+        let num_wires = r1cs.num_wires();
+        let mut constraints = r1cs.constraints;
+        // Like bellman's generator and prover, bind every input to the proof
+        // with `input * 0 = 0`.
+        for wire in 0..num_inputs {
+            constraints.push(Constraint {
+                a: vec![(wire, Scalar::one())],
+                b: vec![],
+                c: vec![],
+            });
+        }
+
+        let m = constraints.len().next_power_of_two();
+        if m > phase1.size() {
+            return Err(Error::InvalidLength {
+                what: "powers of tau",
+                min: m,
+                max: usize::MAX,
+                got: phase1.size(),
+            });
+        }
+        let worker = Worker::new();
+        let tau_g1 = lagrange::<G1Projective>(&phase1.tau_g1[..m], &worker)?;
+        let tau_g2 = lagrange::<G2Projective>(&phase1.tau_g2[..m], &worker)?;
+        let alpha_tau_g1 = lagrange::<G1Projective>(&phase1.alpha_tau_g1[..m], &worker)?;
+        let beta_tau_g1 = lagrange::<G1Projective>(&phase1.beta_tau_g1[..m], &worker)?;
+
+        // The QAP polynomials of every wire at tau, and their combination
+        // `beta u_i + alpha v_i + w_i`.
+        let mut a = vec![G1Projective::identity(); num_wires];
+        let mut b_g1 = vec![G1Projective::identity(); num_wires];
+        let mut b_g2 = vec![G2Projective::identity(); num_wires];
+        let mut ext = vec![G1Projective::identity(); num_wires];
+        for (i, constraint) in constraints.iter().enumerate() {
+            for &(wire, coeff) in &constraint.a {
+                a[wire] += tau_g1[i] * coeff;
+                ext[wire] += beta_tau_g1[i] * coeff;
+            }
+            for &(wire, coeff) in &constraint.b {
+                b_g1[wire] += tau_g1[i] * coeff;
+                b_g2[wire] += tau_g2[i] * coeff;
+                ext[wire] += alpha_tau_g1[i] * coeff;
+            }
+            for &(wire, coeff) in &constraint.c {
+                ext[wire] += tau_g1[i] * coeff;
+            }
+        }
+
+        // tau^i t(tau) = tau^(i + m) - tau^i.
+        let h = (0..m - 1)
+            .map(|i| phase1.tau_g1[i + m] - G1Projective::from(phase1.tau_g1[i]))
+            .collect::<Vec<_>>();
+        let l = to_affine(&ext[num_inputs..]);
+        if l.iter().any(|p| bool::from(p.is_identity())) {
+            return Err(SynthesisError::UnconstrainedVariable.into());
+        }
+
+        let params = Parameters {
+            vk: VerifyingKey {
+                alpha_g1: phase1.alpha_tau_g1[0],
+                beta_g1: phase1.beta_tau_g1[0],
+                beta_g2: phase1.beta_g2,
+                gamma_g2: G2Affine::generator(),
+                delta_g1: G1Affine::generator(),
+                delta_g2: G2Affine::generator(),
+                ic: to_affine(&ext[..num_inputs]),
+            },
+            h: Arc::new(to_affine(&h)),
+            l: Arc::new(l),
+            a: without_identity(to_affine(&a)),
+            b_g1: without_identity(to_affine(&b_g1)),
+            b_g2: without_identity(to_affine(&b_g2)),
+        };
+        let phase1 = phase1.digest();
+        Ok(MpcParameters {
+            config: *config,
+            phase1,
+            start: Self::start(config, &phase1)?,
+            params,
+            contributions: vec![],
+        })
+    }
+
+    fn start(config: &CircuitConfig, phase1: &[u8; 32]) -> Result<[u8; 32]> {
+        Ok(hash(&[b"xor-sum parameters", &config.digest()?, phase1]))
+    }
+
+    pub fn config(&self) -> &CircuitConfig {
+        &self.config
+    }
+
+    /// The parameters after the last contribution. Only trust them once
+    /// [`verify`](Self::verify) succeeds.
+    pub fn params(&self) -> &Parameters<Bls12> {
+        &self.params
+    }
+
+    pub fn num_contributions(&self) -> usize {
+        self.contributions.len()
+    }
+
+    /// Hash of the chain before each contribution, then after the last one.
+    fn chain(&self) -> Transcript {
+        let mut chain = vec![self.start];
+        for key in &self.contributions {
+            let mut encoded = vec![];
+            key.write(&mut encoded)
+                .expect("writing to a Vec never fails");
+            chain.push(hash(&[chain.last().unwrap(), &encoded]));
+        }
+        chain
+    }
+
+    /// Hash of the chain after the last contribution.
+    pub fn digest(&self) -> [u8; 32] {
+        *self.chain().last().unwrap()
+    }
+
+    /// Multiplies `delta` by a secret drawn from `rng`, and returns the new
+    /// hash of the chain. The secret is dropped on return.
+    pub fn contribute<R: RngCore>(&mut self, rng: &mut R) -> [u8; 32] {
+        let previous = self.digest();
+        let delta = random_secret(rng);
+        let delta_inverse = delta.invert().unwrap();
+
+        let params = &mut self.params;
+        params.vk.delta_g1 = (params.vk.delta_g1 * delta).to_affine();
+        params.vk.delta_g2 = (params.vk.delta_g2 * delta).to_affine();
+        params.h = scale(&params.h, delta_inverse);
+        params.l = scale(&params.l, delta_inverse);
+
+        self.contributions.push(PublicKey {
+            delta_g1: params.vk.delta_g1,
+            delta: Knowledge::new(delta, &previous, b"delta", rng),
+        });
+        self.digest()
+    }
+
+    /// Checks that the parameters were computed from `phase1`, then only
+    /// changed by the contributions, and returns the transcript. `phase1`
+    /// must be verified too. `rng` only draws the coefficients that batch the
+    /// checks.
+    pub fn verify<R: RngCore>(&self, phase1: &PowersOfTau, rng: &mut R) -> Result<Transcript> {
+        let invalid = |msg: &str| Err(Error::Ceremony(msg.to_string()));
+        if self.phase1 != phase1.digest() {
+            return invalid("phase 2 started from other powers of tau");
+        }
+        let initial = MpcParameters::new(&self.config, phase1)?.params;
+
+        let chain = self.chain();
+        let mut delta = G1Affine::generator();
+        for (i, (key, previous)) in self.contributions.iter().zip(&chain).enumerate() {
+            check_step(i + 1, &key.delta, previous, "delta", (delta, key.delta_g1))?;
+            delta = key.delta_g1;
+        }
+
+        let (params, vk) = (&self.params, &self.params.vk);
+        if vk.delta_g1 != delta {
+            return invalid("delta doesn't match the last contribution");
+        }
+        if !same_ratio(
+            (G1Affine::generator(), vk.delta_g1),
+            (G2Affine::generator(), vk.delta_g2),
+        ) {
+            return invalid("delta_g1 and delta_g2 don't match");
+        }
+        let unchanged = vk.alpha_g1 == initial.vk.alpha_g1
+            && vk.beta_g1 == initial.vk.beta_g1
+            && vk.beta_g2 == initial.vk.beta_g2
+            && vk.gamma_g2 == initial.vk.gamma_g2
+            && vk.ic == initial.vk.ic
+            && params.a == initial.a
+            && params.b_g1 == initial.b_g1
+            && params.b_g2 == initial.b_g2
+            && params.h.len() == initial.h.len()
+            && params.l.len() == initial.l.len();
+        if !unchanged {
+            return invalid("parameters other than delta changed");
+        }
+        let delta_g2 = (vk.delta_g2, G2Affine::generator());
+        if !same_ratio(merge_pairs(&initial.h, &params.h, rng), delta_g2) {
+            return invalid("h isn't divided by delta");
+        }
+        if !same_ratio(merge_pairs(&initial.l, &params.l, rng), delta_g2) {
+            return invalid("l isn't divided by delta");
+        }
+
+        Ok(chain[1..].to_vec())
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        write_header(&mut writer, MAGIC, &self.config)?;
+        writer.write_all(&self.phase1)?;
+        self.params.write(&mut writer)?;
+        writer.write_all(&(self.contributions.len() as u32).to_be_bytes())?;
+        for key in &self.contributions {
+            key.write(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the parameters of a ceremony. They are only known to come from
+    /// it once [`verify`](Self::verify) succeeds.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self> {
+        let config = read_header(&mut reader, MAGIC)?;
+        let mut phase1 = [0u8; 32];
+        reader.read_exact(&mut phase1)?;
+        let params = Parameters::read(&mut reader, true)?;
+        let mut contributions = vec![];
+        for _ in 0..read_u32(&mut reader)? {
+            contributions.push(PublicKey::read(&mut reader)?);
+        }
+
+        Ok(MpcParameters {
+            config,
+            phase1,
+            start: Self::start(&config, &phase1)?,
+            params,
+            contributions,
+        })
+    }
+}
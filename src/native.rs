@@ -0,0 +1,45 @@
+use crate::{Error, Result};
+
+/// Native (out-of-circuit) xor-sum of any number of bytes.
+pub fn native_xor_sum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |prev, next| prev ^ next)
+}
+
+/// Native xor-sum of the first `length` bytes of `data`, the reference for
+/// variable-length circuits where the rest of the buffer is padding.
+pub fn native_xor_sum_prefix(data: &[u8], length: usize) -> Result<u8> {
+    Ok(native_xor_sum(prefix(data, length)?))
+}
+
+fn prefix(data: &[u8], length: usize) -> Result<&[u8]> {
+    data.get(..length).ok_or(Error::InvalidLength {
+        what: "bytes",
+        min: 0,
+        max: data.len(),
+        got: length,
+    })
+}
+
+/// Native xor-fold: byte `i` of `data` is XORed into byte `i % output_size` of
+/// the digest.
+pub fn native_xor_fold(data: &[u8], output_size: usize) -> Result<Vec<u8>> {
+    if output_size == 0 {
+        return Err(Error::InvalidLength {
+            what: "digest bytes",
+            min: 1,
+            max: usize::MAX,
+            got: 0,
+        });
+    }
+    let mut digest = vec![0; output_size];
+    for (i, byte) in data.iter().enumerate() {
+        digest[i % output_size] ^= byte;
+    }
+    Ok(digest)
+}
+
+/// Native xor-fold of the first `length` bytes of `data`, see
+/// [`native_xor_sum_prefix`].
+pub fn native_xor_fold_prefix(data: &[u8], length: usize, output_size: usize) -> Result<Vec<u8>> {
+    native_xor_fold(prefix(data, length)?, output_size)
+}
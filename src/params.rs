@@ -0,0 +1,273 @@
+//! Versioned on-disk format for Groth16 parameters and verifying keys.
+//!
+//! Every file starts with a header:
+//!
+//! ```text
+//! magic      : 8 bytes, "XSUMPRMS" for parameters or "XSUMVKEY" for verifying keys
+//! version    : u32, big-endian
+//! input size : u32, big-endian, the (maximum) preimage length in bytes
+//! output size: u32, big-endian, the digest length in bytes
+//! flags      : u8, bit 0 is set for variable-length preimages, bit 1 for
+//!              the linear xor gadget
+//! circuit    : 32 bytes, digest of the circuit shape (see `CircuitConfig::digest`)
+//! ```
+//!
+//! followed by bellman's own encoding of `Parameters` or `VerifyingKey`.
+//! Loading fails if the digest doesn't match the current [`MyCircuit`] with
+//! the recorded configuration, so a file generated for a different circuit is
+//! rejected up front instead of producing proofs that never verify.
+//!
+//! The proving parameters can also be mapped into memory instead of read, see
+//! [`map_parameters`].
+//!
+//! [`MyCircuit`]: crate::MyCircuit
+
+use std::{
+    convert::TryInto,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+#[cfg(feature = "prover")]
+use bellman::groth16;
+use bellman::groth16::{Parameters, VerifyingKey};
+use blake2s_simd::Params as Blake2sParams;
+use bls12_381::Bls12;
+#[cfg(feature = "prover")]
+use rand_core::RngCore;
+
+#[cfg(feature = "prover")]
+use crate::MyCircuit;
+use crate::{CircuitConfig, Error, Result, XorGadget};
+
+#[cfg(feature = "prover")]
+mod mapped;
+
+#[cfg(feature = "prover")]
+pub use mapped::{map_parameters, MappedBases, MappedParameters, MappedSource};
+
+/// Current version of the file format.
+pub const FORMAT_VERSION: u32 = 4;
+
+const PARAMS_MAGIC: &[u8; 8] = b"XSUMPRMS";
+const VK_MAGIC: &[u8; 8] = b"XSUMVKEY";
+
+const FLAG_VARIABLE_LENGTH: u8 = 1;
+const FLAG_LINEAR_GADGET: u8 = 2;
+
+/// Fingerprint of a verifying key: the BLAKE2s digest of its encoding.
+pub fn verifying_key_fingerprint(vk: &VerifyingKey<Bls12>) -> [u8; 32] {
+    let mut encoded = vec![];
+    vk.write(&mut encoded)
+        .expect("writing to a Vec never fails");
+
+    let mut fingerprint = [0; 32];
+    fingerprint.copy_from_slice(
+        Blake2sParams::new()
+            .hash_length(32)
+            .hash(&encoded)
+            .as_bytes(),
+    );
+    fingerprint
+}
+
+fn read_u32<R: Read>(mut reader: R) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+pub(crate) fn write_header<W: Write>(
+    mut writer: W,
+    magic: &[u8; 8],
+    config: &CircuitConfig,
+) -> Result<()> {
+    let too_large = |what, got| Error::InvalidLength {
+        what,
+        min: 0,
+        max: u32::MAX as usize,
+        got,
+    };
+    let input_size: u32 = config
+        .input_size
+        .try_into()
+        .map_err(|_| too_large("preimage bytes", config.input_size))?;
+    let output_size: u32 = config
+        .output_size
+        .try_into()
+        .map_err(|_| too_large("digest bytes", config.output_size))?;
+
+    writer.write_all(magic)?;
+    writer.write_all(&FORMAT_VERSION.to_be_bytes())?;
+    writer.write_all(&input_size.to_be_bytes())?;
+    writer.write_all(&output_size.to_be_bytes())?;
+    let mut flags = 0;
+    if config.variable_length {
+        flags |= FLAG_VARIABLE_LENGTH;
+    }
+    if config.gadget == XorGadget::Linear {
+        flags |= FLAG_LINEAR_GADGET;
+    }
+    writer.write_all(&[flags])?;
+    writer.write_all(&config.digest()?)?;
+    Ok(())
+}
+
+pub(crate) fn read_header<R: Read>(mut reader: R, magic: &[u8; 8]) -> Result<CircuitConfig> {
+    let invalid = Error::Decode;
+
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    if &buf != magic {
+        return Err(invalid(format!(
+            "bad magic, expected {:?}",
+            String::from_utf8_lossy(magic)
+        )));
+    }
+
+    let version = read_u32(&mut reader)?;
+    if version != FORMAT_VERSION {
+        return Err(invalid(format!(
+            "unsupported format version {}, expected {}",
+            version, FORMAT_VERSION
+        )));
+    }
+
+    let input_size = read_u32(&mut reader)? as usize;
+    let output_size = read_u32(&mut reader)? as usize;
+    if output_size == 0 {
+        return Err(invalid("output size is zero".to_string()));
+    }
+    let mut flags = [0u8; 1];
+    reader.read_exact(&mut flags)?;
+    if flags[0] & !(FLAG_VARIABLE_LENGTH | FLAG_LINEAR_GADGET) != 0 {
+        return Err(invalid(format!("unknown flags {:#04x}", flags[0])));
+    }
+    let config = CircuitConfig {
+        input_size,
+        variable_length: flags[0] & FLAG_VARIABLE_LENGTH != 0,
+        output_size,
+        gadget: if flags[0] & FLAG_LINEAR_GADGET != 0 {
+            XorGadget::Linear
+        } else {
+            XorGadget::Boolean
+        },
+    };
+
+    // Bounds the sizes before synthesizing the circuit for the digest.
+    config.check()?;
+
+    let mut digest = [0u8; 32];
+    reader.read_exact(&mut digest)?;
+    if digest != config.digest()? {
+        return Err(invalid(
+            "file was generated for a different circuit".to_string(),
+        ));
+    }
+
+    Ok(config)
+}
+
+/// Runs the trusted setup of [`MyCircuit`] with `config`. The randomness of
+/// `rng` is toxic waste: whoever knows it can forge proofs.
+#[cfg(feature = "prover")]
+pub fn generate_parameters<R: RngCore>(
+    config: &CircuitConfig,
+    rng: &mut R,
+) -> Result<Parameters<Bls12>> {
+    config.check()?;
+    Ok(groth16::generate_random_parameters(
+        MyCircuit::new(*config),
+        rng,
+    )?)
+}
+
+/// Writes the full proving parameters generated for `config`.
+pub fn write_parameters<W: Write>(
+    config: &CircuitConfig,
+    params: &Parameters<Bls12>,
+    mut writer: W,
+) -> Result<()> {
+    write_header(&mut writer, PARAMS_MAGIC, config)?;
+    Ok(params.write(&mut writer)?)
+}
+
+/// Reads the full proving parameters and the configuration they were
+/// generated for. If `checked` is set, every point is checked to be on the
+/// curve and in the right subgroup, which is slow.
+pub fn read_parameters<R: Read>(
+    mut reader: R,
+    checked: bool,
+) -> Result<(CircuitConfig, Parameters<Bls12>)> {
+    let config = read_header(&mut reader, PARAMS_MAGIC)?;
+    Ok((config, Parameters::read(&mut reader, checked)?))
+}
+
+/// Fails if `vk` lacks `beta_g1` or `delta_g1`, as keys decoded from
+/// snarkjs do. Such a key verifies proofs, but its fingerprint isn't the one
+/// of the key the proofs were made for.
+fn check_complete(vk: &VerifyingKey<Bls12>) -> Result<()> {
+    if bool::from(vk.beta_g1.is_identity()) || bool::from(vk.delta_g1.is_identity()) {
+        return Err(Error::decode(
+            "verifying key lacks beta_g1 or delta_g1, it can only verify",
+        ));
+    }
+    Ok(())
+}
+
+/// Writes a standalone verifying key generated for `config`. Keys decoded
+/// from snarkjs are rejected, see
+/// [`verifying_key_from_json`](crate::snarkjs::verifying_key_from_json).
+pub fn write_verifying_key<W: Write>(
+    config: &CircuitConfig,
+    vk: &VerifyingKey<Bls12>,
+    mut writer: W,
+) -> Result<()> {
+    check_complete(vk)?;
+    write_header(&mut writer, VK_MAGIC, config)?;
+    Ok(vk.write(&mut writer)?)
+}
+
+/// Reads a standalone verifying key and the configuration it was generated
+/// for.
+pub fn read_verifying_key<R: Read>(mut reader: R) -> Result<(CircuitConfig, VerifyingKey<Bls12>)> {
+    let config = read_header(&mut reader, VK_MAGIC)?;
+    Ok((config, VerifyingKey::read(&mut reader)?))
+}
+
+/// Saves the full proving parameters to `path`.
+pub fn save_parameters<P: AsRef<Path>>(
+    path: P,
+    config: &CircuitConfig,
+    params: &Parameters<Bls12>,
+) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_parameters(config, params, &mut writer)?;
+    Ok(writer.flush()?)
+}
+
+/// Loads the full proving parameters from `path`.
+pub fn load_parameters<P: AsRef<Path>>(
+    path: P,
+    checked: bool,
+) -> Result<(CircuitConfig, Parameters<Bls12>)> {
+    read_parameters(BufReader::new(File::open(path)?), checked)
+}
+
+/// Saves a standalone verifying key to `path`.
+pub fn save_verifying_key<P: AsRef<Path>>(
+    path: P,
+    config: &CircuitConfig,
+    vk: &VerifyingKey<Bls12>,
+) -> Result<()> {
+    check_complete(vk)?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_verifying_key(config, vk, &mut writer)?;
+    Ok(writer.flush()?)
+}
+
+/// Loads a standalone verifying key from `path`.
+pub fn load_verifying_key<P: AsRef<Path>>(path: P) -> Result<(CircuitConfig, VerifyingKey<Bls12>)> {
+    read_verifying_key(BufReader::new(File::open(path)?))
+}
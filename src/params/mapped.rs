@@ -0,0 +1,283 @@
+//! Proving parameters read lazily from a memory-mapped file.
+//!
+//! bellman reads every point of the parameters into memory up front. A
+//! [`MappedParameters`] instead maps the file and decodes each point when the
+//! prover's multi-exponentiations get to it, so the parameters stay in the page
+//! cache, shared by every prover on the machine, and are never copied to the
+//! heap.
+
+use std::{fs::File, io, marker::PhantomData, path::Path, sync::Arc};
+
+use bellman::{
+    groth16::{ParameterSource, VerifyingKey},
+    multiexp::{Source, SourceBuilder},
+    SynthesisError,
+};
+use bls12_381::{Bls12, G1Affine, G2Affine};
+use group::{prime::PrimeCurveAffine, UncompressedEncoding};
+use memmap2::Mmap;
+
+use super::{read_header, PARAMS_MAGIC};
+use crate::{CircuitConfig, Error, Result};
+
+/// Proving parameters in a file written by
+/// [`save_parameters`](super::save_parameters), mapped into memory. Use a
+/// reference to it in place of a `&Parameters` with
+/// `groth16::create_random_proof`.
+pub struct MappedParameters {
+    map: Arc<Mmap>,
+    vk: VerifyingKey<Bls12>,
+    h: Query,
+    l: Query,
+    a: Query,
+    b_g1: Query,
+    b_g2: Query,
+}
+
+/// Location of the points of one query in the file.
+#[derive(Clone, Copy)]
+struct Query {
+    offset: usize,
+    len: usize,
+}
+
+/// Size of the uncompressed encoding of a `G`.
+fn point_size<G: UncompressedEncoding>() -> usize {
+    G::Uncompressed::default().as_ref().len()
+}
+
+fn decode<G: UncompressedEncoding>(bytes: &[u8], checked: bool) -> Option<G> {
+    let mut repr = G::Uncompressed::default();
+    repr.as_mut().copy_from_slice(bytes);
+    if checked {
+        G::from_uncompressed(&repr).into()
+    } else {
+        G::from_uncompressed_unchecked(&repr).into()
+    }
+}
+
+/// Maps the proving parameters at `path`. The file must not be modified while
+/// the parameters are in use.
+///
+/// Only the header and the verifying key are read here. If `checked` is set,
+/// every point is also decoded once and checked to be on the curve and in the
+/// right subgroup, which is slow, but still doesn't keep them in memory.
+/// Otherwise a malformed point is only reported when a proof needs it.
+pub fn map_parameters<P: AsRef<Path>>(
+    path: P,
+    checked: bool,
+) -> Result<(CircuitConfig, MappedParameters)> {
+    let file = File::open(path)?;
+    // SAFETY: the map is only ever read, and the caller guarantees that the
+    // file doesn't change under it.
+    let map = unsafe { Mmap::map(&file)? };
+
+    let mut reader = &map[..];
+    let config = read_header(&mut reader, PARAMS_MAGIC)?;
+    let vk = VerifyingKey::read(&mut reader)?;
+    let mut offset = map.len() - reader.len();
+
+    let h = Query::read::<G1Affine>(&map, &mut offset, checked)?;
+    let l = Query::read::<G1Affine>(&map, &mut offset, checked)?;
+    let a = Query::read::<G1Affine>(&map, &mut offset, checked)?;
+    let b_g1 = Query::read::<G1Affine>(&map, &mut offset, checked)?;
+    let b_g2 = Query::read::<G2Affine>(&map, &mut offset, checked)?;
+    if offset != map.len() {
+        return Err(Error::decode("trailing data after the parameters"));
+    }
+
+    Ok((
+        config,
+        MappedParameters {
+            map: Arc::new(map),
+            vk,
+            h,
+            l,
+            a,
+            b_g1,
+            b_g2,
+        },
+    ))
+}
+
+impl Query {
+    /// Reads the length of a query of `G` points at `offset`, and moves
+    /// `offset` past its points.
+    fn read<G: UncompressedEncoding + PrimeCurveAffine>(
+        map: &[u8],
+        offset: &mut usize,
+        checked: bool,
+    ) -> Result<Self> {
+        let truncated = || Error::decode("parameters are truncated");
+
+        let len = map.get(*offset..*offset + 4).ok_or_else(truncated)?;
+        let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+        let start = *offset + 4;
+        let end = len
+            .checked_mul(point_size::<G>())
+            .and_then(|size| start.checked_add(size))
+            .filter(|&end| end <= map.len())
+            .ok_or_else(truncated)?;
+
+        if checked {
+            for bytes in map[start..end].chunks(point_size::<G>()) {
+                let point =
+                    decode::<G>(bytes, true).ok_or_else(|| Error::decode("invalid point"))?;
+                if bool::from(point.is_identity()) {
+                    return Err(Error::decode("point at infinity"));
+                }
+            }
+        }
+
+        *offset = end;
+        Ok(Query { offset: start, len })
+    }
+
+    fn bases<G>(self, map: &Arc<Mmap>, start: usize) -> MappedBases<G> {
+        MappedBases {
+            map: map.clone(),
+            query: self,
+            start,
+            point: PhantomData,
+        }
+    }
+}
+
+impl MappedParameters {
+    pub fn vk(&self) -> &VerifyingKey<Bls12> {
+        &self.vk
+    }
+}
+
+/// The points of a query, starting at `start`, as a source for bellman's
+/// multi-exponentiation.
+pub struct MappedBases<G> {
+    map: Arc<Mmap>,
+    query: Query,
+    start: usize,
+    point: PhantomData<G>,
+}
+
+impl<G> Clone for MappedBases<G> {
+    fn clone(&self) -> Self {
+        self.query.bases(&self.map, self.start)
+    }
+}
+
+impl<G> SourceBuilder<G> for MappedBases<G>
+where
+    G: UncompressedEncoding + PrimeCurveAffine,
+{
+    type Source = MappedSource<G>;
+
+    fn new(self) -> MappedSource<G> {
+        MappedSource {
+            next: self.start,
+            bases: self,
+            point: G::identity(),
+        }
+    }
+}
+
+/// Decodes the points of [`MappedBases`] one at a time.
+pub struct MappedSource<G> {
+    bases: MappedBases<G>,
+    next: usize,
+    /// The last point decoded, which `next` hands out a reference to.
+    point: G,
+}
+
+fn no_more_bases() -> SynthesisError {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "expected more bases from source",
+    )
+    .into()
+}
+
+impl<G> Source<G> for MappedSource<G>
+where
+    G: UncompressedEncoding + PrimeCurveAffine,
+{
+    fn next(&mut self) -> std::result::Result<&G, SynthesisError> {
+        let Query { offset, len } = self.bases.query;
+        if self.next >= len {
+            return Err(no_more_bases());
+        }
+
+        let size = point_size::<G>();
+        let start = offset + self.next * size;
+        self.point = decode(&self.bases.map[start..start + size], false).ok_or_else(|| {
+            SynthesisError::from(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid point in the parameters",
+            ))
+        })?;
+        if bool::from(self.point.is_identity()) {
+            return Err(SynthesisError::UnexpectedIdentity);
+        }
+
+        self.next += 1;
+        Ok(&self.point)
+    }
+
+    fn skip(&mut self, amt: usize) -> std::result::Result<(), SynthesisError> {
+        if self.next >= self.bases.query.len {
+            return Err(no_more_bases());
+        }
+        self.next += amt;
+        Ok(())
+    }
+}
+
+/// Same queries as bellman's `&Parameters`: the `a` and `b` queries hold the
+/// points of the inputs followed by those of the auxiliary variables.
+impl ParameterSource<Bls12> for &MappedParameters {
+    type G1Builder = MappedBases<G1Affine>;
+    type G2Builder = MappedBases<G2Affine>;
+
+    fn get_vk(&mut self, _: usize) -> std::result::Result<VerifyingKey<Bls12>, SynthesisError> {
+        Ok(self.vk.clone())
+    }
+
+    fn get_h(&mut self, _: usize) -> std::result::Result<Self::G1Builder, SynthesisError> {
+        Ok(self.h.bases(&self.map, 0))
+    }
+
+    fn get_l(&mut self, _: usize) -> std::result::Result<Self::G1Builder, SynthesisError> {
+        Ok(self.l.bases(&self.map, 0))
+    }
+
+    fn get_a(
+        &mut self,
+        num_inputs: usize,
+        _: usize,
+    ) -> std::result::Result<(Self::G1Builder, Self::G1Builder), SynthesisError> {
+        Ok((
+            self.a.bases(&self.map, 0),
+            self.a.bases(&self.map, num_inputs),
+        ))
+    }
+
+    fn get_b_g1(
+        &mut self,
+        num_inputs: usize,
+        _: usize,
+    ) -> std::result::Result<(Self::G1Builder, Self::G1Builder), SynthesisError> {
+        Ok((
+            self.b_g1.bases(&self.map, 0),
+            self.b_g1.bases(&self.map, num_inputs),
+        ))
+    }
+
+    fn get_b_g2(
+        &mut self,
+        num_inputs: usize,
+        _: usize,
+    ) -> std::result::Result<(Self::G2Builder, Self::G2Builder), SynthesisError> {
+        Ok((
+            self.b_g2.bases(&self.map, 0),
+            self.b_g2.bases(&self.map, num_inputs),
+        ))
+    }
+}
@@ -0,0 +1,249 @@
+//! Self-describing proof bundles that can be sent to a verifier.
+//!
+//! A bundle carries the Groth16 proof together with everything needed to check
+//! it: the public hash and the fingerprint of the verifying key the proof was
+//! made for. It has a binary encoding:
+//!
+//! ```text
+//! magic          : 8 bytes, "XSUMPROF"
+//! version        : u32, big-endian
+//! vk fingerprint : 32 bytes
+//! hash length    : u32, big-endian
+//! hash           : `hash length` bytes
+//! proof          : 192 bytes, compressed A, B and C points
+//! ```
+//!
+//! and an equivalent JSON encoding with every byte string hex-encoded.
+
+use std::{
+    convert::TryInto,
+    io::{Read, Write},
+};
+
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use bellman::groth16;
+#[cfg(feature = "verifier")]
+use bellman::groth16::PreparedVerifyingKey;
+#[cfg(feature = "prover")]
+use bellman::groth16::{ParameterSource, Parameters};
+use bellman::{
+    gadgets::multipack,
+    groth16::{Proof, VerifyingKey},
+};
+use bls12_381::{Bls12, Scalar};
+#[cfg(feature = "prover")]
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "prover")]
+use crate::{native_xor_fold, CircuitConfig, MyCircuit};
+use crate::{params::verifying_key_fingerprint, Error, Result};
+
+/// Current version of the bundle format.
+pub const BUNDLE_VERSION: u32 = 2;
+
+const BUNDLE_MAGIC: &[u8; 8] = b"XSUMPROF";
+
+/// A proof together with its public input and the verifying key it targets.
+#[derive(Clone, PartialEq)]
+pub struct ProofBundle {
+    pub proof: Proof<Bls12>,
+    /// The xor-fold digest of the preimage, the public input of the circuit.
+    pub hash: Vec<u8>,
+    /// Fingerprint of the verifying key, see [`verifying_key_fingerprint`].
+    pub vk_fingerprint: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonBundle {
+    version: u32,
+    vk_fingerprint: String,
+    hash: String,
+    proof: JsonProof,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonProof {
+    a: String,
+    b: String,
+    c: String,
+}
+
+fn invalid<E: ToString>(e: E) -> Error {
+    Error::Decode(e.to_string())
+}
+
+fn decode_hex<const N: usize>(s: &str) -> Result<[u8; N]> {
+    let bytes = hex::decode(s).map_err(invalid)?;
+    let len = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| invalid(format!("expected {} bytes, got {}", N, len)))
+}
+
+impl ProofBundle {
+    pub fn new(proof: Proof<Bls12>, hash: Vec<u8>, vk: &VerifyingKey<Bls12>) -> Self {
+        ProofBundle {
+            proof,
+            hash,
+            vk_fingerprint: verifying_key_fingerprint(vk),
+        }
+    }
+
+    /// Proves knowledge of `preimage` with `params`, generated for `config`.
+    #[cfg(feature = "prover")]
+    pub fn create<R: RngCore>(
+        params: &Parameters<Bls12>,
+        config: &CircuitConfig,
+        preimage: Vec<u8>,
+        rng: &mut R,
+    ) -> Result<Self> {
+        ProofBundle::create_with_source(params, &params.vk, config, preimage, rng)
+    }
+
+    /// Like [`ProofBundle::create`], with the parameters from any source,
+    /// e.g. [`MappedParameters`](crate::params::MappedParameters). `vk` is
+    /// their verifying key.
+    #[cfg(feature = "prover")]
+    pub fn create_with_source<P: ParameterSource<Bls12>, R: RngCore>(
+        params: P,
+        vk: &VerifyingKey<Bls12>,
+        config: &CircuitConfig,
+        preimage: Vec<u8>,
+        rng: &mut R,
+    ) -> Result<Self> {
+        config.check()?;
+        config.check_preimage(&preimage)?;
+        let hash = native_xor_fold(&preimage, config.output_size)?;
+        let c = MyCircuit::with_preimage(*config, preimage);
+        let proof = groth16::create_random_proof(c, params, rng)?;
+        Ok(ProofBundle::new(proof, hash, vk))
+    }
+
+    /// Public inputs of the proof, as expected by `groth16::verify_proof`,
+    /// for a circuit with `output_size` digest bytes.
+    ///
+    /// Packing ignores trailing zero bits, so a hash padded with zero bytes
+    /// would give the same inputs: its length is checked first.
+    pub fn public_inputs(&self, output_size: usize) -> Result<Vec<Scalar>> {
+        if self.hash.len() != output_size {
+            return Err(Error::length("hash bytes", output_size, self.hash.len()));
+        }
+        Ok(multipack::compute_multipacking(
+            &multipack::bytes_to_bits_le(&self.hash),
+        ))
+    }
+
+    /// Returns `true` if the bundle was made for `vk`.
+    pub fn is_for(&self, vk: &VerifyingKey<Bls12>) -> bool {
+        self.vk_fingerprint == verifying_key_fingerprint(vk)
+    }
+
+    /// Verifies the proof against its public input. `pvk` must be prepared
+    /// from a key the bundle was made for, which the caller checks with
+    /// [`ProofBundle::is_for`], and `output_size` is that of its
+    /// configuration.
+    #[cfg(feature = "verifier")]
+    pub fn verify(&self, pvk: &PreparedVerifyingKey<Bls12>, output_size: usize) -> Result<()> {
+        Ok(groth16::verify_proof(
+            pvk,
+            &self.proof,
+            &self.public_inputs(output_size)?,
+        )?)
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(BUNDLE_MAGIC)?;
+        writer.write_all(&BUNDLE_VERSION.to_be_bytes())?;
+        writer.write_all(&self.vk_fingerprint)?;
+        let hash_len: u32 = self
+            .hash
+            .len()
+            .try_into()
+            .map_err(|_| Error::InvalidLength {
+                what: "hash bytes",
+                min: 0,
+                max: u32::MAX as usize,
+                got: self.hash.len(),
+            })?;
+        writer.write_all(&hash_len.to_be_bytes())?;
+        writer.write_all(&self.hash)?;
+        Ok(self.proof.write(&mut writer)?)
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != BUNDLE_MAGIC {
+            return Err(invalid("not a proof bundle"));
+        }
+
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_be_bytes(version);
+        if version != BUNDLE_VERSION {
+            return Err(invalid(format!(
+                "unsupported bundle version {}, expected {}",
+                version, BUNDLE_VERSION
+            )));
+        }
+
+        let mut vk_fingerprint = [0u8; 32];
+        reader.read_exact(&mut vk_fingerprint)?;
+        let mut hash_len = [0u8; 4];
+        reader.read_exact(&mut hash_len)?;
+        let mut hash = vec![];
+        // Don't trust the length for preallocation, a bogus one would just
+        // hit the end of the input.
+        (&mut reader)
+            .take(u32::from_be_bytes(hash_len).into())
+            .read_to_end(&mut hash)?;
+        if hash.len() != u32::from_be_bytes(hash_len) as usize {
+            return Err(invalid("proof bundle is truncated"));
+        }
+        let proof = Proof::read(&mut reader)?;
+
+        Ok(ProofBundle {
+            proof,
+            hash,
+            vk_fingerprint,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        let json = JsonBundle {
+            version: BUNDLE_VERSION,
+            vk_fingerprint: hex::encode(self.vk_fingerprint),
+            hash: hex::encode(&self.hash),
+            proof: JsonProof {
+                a: hex::encode(self.proof.a.to_compressed()),
+                b: hex::encode(self.proof.b.to_compressed()),
+                c: hex::encode(self.proof.c.to_compressed()),
+            },
+        };
+        serde_json::to_string_pretty(&json).expect("bundle always serializes")
+    }
+
+    pub fn from_json(s: &str) -> Result<Self> {
+        let json: JsonBundle = serde_json::from_str(s).map_err(invalid)?;
+        if json.version != BUNDLE_VERSION {
+            return Err(invalid(format!(
+                "unsupported bundle version {}, expected {}",
+                json.version, BUNDLE_VERSION
+            )));
+        }
+
+        // Reuse bellman's decoding, so points get the same validation as in
+        // the binary format.
+        let mut proof = Vec::with_capacity(192);
+        proof.extend_from_slice(&decode_hex::<48>(&json.proof.a)?);
+        proof.extend_from_slice(&decode_hex::<96>(&json.proof.b)?);
+        proof.extend_from_slice(&decode_hex::<48>(&json.proof.c)?);
+
+        Ok(ProofBundle {
+            proof: Proof::read(&proof[..])?,
+            hash: hex::decode(&json.hash).map_err(invalid)?,
+            vk_fingerprint: decode_hex(&json.vk_fingerprint)?,
+        })
+    }
+}
@@ -0,0 +1,92 @@
+use bellman::{Circuit, ConstraintSystem, LinearCombination, SynthesisError};
+use ff::PrimeField;
+
+use super::R1cs;
+use crate::Error;
+
+/// Adapter that synthesizes an [`R1cs`], typically imported from another
+/// toolchain, as a bellman circuit.
+///
+/// Wire 0 is bellman's "one", the public wires become its public inputs in
+/// order, and the private wires its auxiliary variables. Synthesis fails if a
+/// constraint refers to a wire out of range.
+#[derive(Clone, Debug)]
+pub struct R1csCircuit<Scalar: PrimeField> {
+    pub r1cs: R1cs<Scalar>,
+    /// The assignment of every wire, or `None` for setup and verification.
+    pub witness: Option<Vec<Scalar>>,
+}
+
+impl<Scalar: PrimeField> R1csCircuit<Scalar> {
+    pub fn new(r1cs: R1cs<Scalar>) -> Self {
+        R1csCircuit {
+            r1cs,
+            witness: None,
+        }
+    }
+
+    /// Fails if the witness doesn't assign every wire, or doesn't set wire 0
+    /// to one. The constraints are only checked when proving.
+    pub fn with_witness(r1cs: R1cs<Scalar>, witness: Vec<Scalar>) -> crate::Result<Self> {
+        if witness.len() != r1cs.num_wires() {
+            return Err(Error::length(
+                "witness values",
+                r1cs.num_wires(),
+                witness.len(),
+            ));
+        }
+        if witness[0] != Scalar::one() {
+            return Err(Error::decode("wire 0 of the witness is not one"));
+        }
+        Ok(R1csCircuit {
+            r1cs,
+            witness: Some(witness),
+        })
+    }
+
+    /// The public inputs to verify a proof against, if there is a witness.
+    pub fn public_inputs(&self) -> Option<&[Scalar]> {
+        self.witness
+            .as_ref()
+            .map(|witness| &witness[1..=self.r1cs.num_public])
+    }
+}
+
+impl<Scalar: PrimeField> Circuit<Scalar> for R1csCircuit<Scalar> {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let R1csCircuit { r1cs, witness } = self;
+        r1cs.check()?;
+        let value = |wire: usize| {
+            witness
+                .as_ref()
+                .map(|witness| witness[wire])
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+
+        let mut wires = Vec::with_capacity(r1cs.num_wires());
+        wires.push(CS::one());
+        for wire in 1..=r1cs.num_public {
+            wires.push(cs.alloc_input(|| format!("wire {}", wire), || value(wire))?);
+        }
+        for wire in r1cs.num_public + 1..r1cs.num_wires() {
+            wires.push(cs.alloc(|| format!("wire {}", wire), || value(wire))?);
+        }
+
+        let lc = |terms: &[(usize, Scalar)]| {
+            terms
+                .iter()
+                .fold(LinearCombination::<Scalar>::zero(), |lc, &(wire, coeff)| {
+                    lc + (coeff, wires[wire])
+                })
+        };
+        for (i, constraint) in r1cs.constraints.iter().enumerate() {
+            cs.enforce(
+                || format!("constraint {}", i),
+                |zero| zero + &lc(&constraint.a),
+                |zero| zero + &lc(&constraint.b),
+                |zero| zero + &lc(&constraint.c),
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+//! Binary `.r1cs` and `.wtns` formats of iden3, see
+//! <https://github.com/iden3/r1csfile/blob/master/doc/r1cs_bin_format.md>.
+//! All integers are little-endian.
+
+use std::{
+    convert::TryInto,
+    io::{Read, Write},
+};
+
+use ff::PrimeField;
+
+use super::{modulus, Constraint, R1cs};
+use crate::{Error, Result};
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const R1CS_VERSION: u32 = 1;
+const R1CS_HEADER: u32 = 1;
+const R1CS_CONSTRAINTS: u32 = 2;
+const R1CS_WIRE_TO_LABEL: u32 = 3;
+
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+const WTNS_VERSION: u32 = 2;
+const WTNS_HEADER: u32 = 1;
+const WTNS_DATA: u32 = 2;
+
+fn u32_of(n: usize) -> Result<[u8; 4]> {
+    let too_large = Error::InvalidLength {
+        what: "wires, constraints or terms",
+        min: 0,
+        max: u32::MAX as usize,
+        got: n,
+    };
+    let n: u32 = n.try_into().map_err(|_| too_large)?;
+    Ok(n.to_le_bytes())
+}
+
+fn invalid(msg: String) -> Error {
+    Error::Decode(msg)
+}
+
+/// Cursor over the contents of a section.
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Bytes<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(invalid("section is truncated".to_string()));
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> Result<usize> {
+        Ok(self.u32()? as usize)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Reads a field element in the standard little-endian encoding, which
+    /// must be canonical.
+    fn scalar<Scalar: PrimeField>(&mut self) -> Result<Scalar> {
+        let mut repr = Scalar::Repr::default();
+        let len = repr.as_ref().len();
+        repr.as_mut().copy_from_slice(self.take(len)?);
+        Scalar::from_repr(repr).ok_or_else(|| invalid("field element is not canonical".to_string()))
+    }
+
+    /// Reads the field description shared by both formats: the size of an
+    /// element, then the modulus.
+    fn field<Scalar: PrimeField>(&mut self) -> Result<()> {
+        let prime = modulus::<Scalar>();
+        if self.usize()? != prime.len() || self.take(prime.len())? != prime.as_slice() {
+            return Err(invalid("file is for a different field".to_string()));
+        }
+        Ok(())
+    }
+
+    fn finish(&self) -> Result<()> {
+        if !self.0.is_empty() {
+            return Err(invalid("section has trailing bytes".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Reads a whole file in the common container format: magic, version, then
+/// sections of `(type, size, data)`. Every section type may appear only once.
+fn read_sections<R: Read>(
+    mut reader: R,
+    magic: &[u8; 4],
+    version: u32,
+) -> Result<Vec<(u32, Vec<u8>)>> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    let mut bytes = Bytes(&bytes);
+
+    if bytes.take(4)? != magic {
+        return Err(invalid(format!(
+            "bad magic, expected {:?}",
+            String::from_utf8_lossy(magic)
+        )));
+    }
+    let found = bytes.u32()?;
+    if found != version {
+        return Err(invalid(format!(
+            "unsupported version {}, expected {}",
+            found, version
+        )));
+    }
+
+    let count = bytes.u32()?;
+    let mut sections: Vec<(u32, Vec<u8>)> = vec![];
+    for _ in 0..count {
+        let kind = bytes.u32()?;
+        let size = bytes.u64()?;
+        let size = size
+            .try_into()
+            .map_err(|_| invalid("section is too large".to_string()))?;
+        if sections.iter().any(|&(k, _)| k == kind) {
+            return Err(invalid(format!("duplicate section {}", kind)));
+        }
+        sections.push((kind, bytes.take(size)?.to_vec()));
+    }
+    bytes.finish()?;
+    Ok(sections)
+}
+
+fn section(sections: &[(u32, Vec<u8>)], kind: u32) -> Result<Bytes<'_>> {
+    sections
+        .iter()
+        .find(|&&(k, _)| k == kind)
+        .map(|(_, data)| Bytes(data))
+        .ok_or_else(|| invalid(format!("missing section {}", kind)))
+}
+
+fn write_section<W: Write>(mut writer: W, kind: u32, data: &[u8]) -> Result<()> {
+    writer.write_all(&kind.to_le_bytes())?;
+    writer.write_all(&(data.len() as u64).to_le_bytes())?;
+    Ok(writer.write_all(data)?)
+}
+
+/// Writes `r1cs` in the iden3 `.r1cs` format. Every wire is its own label.
+pub fn write_r1cs<Scalar: PrimeField, W: Write>(r1cs: &R1cs<Scalar>, mut writer: W) -> Result<()> {
+    let prime = modulus::<Scalar>();
+
+    let mut header = vec![];
+    header.extend_from_slice(&u32_of(prime.len())?);
+    header.extend_from_slice(&prime);
+    header.extend_from_slice(&u32_of(r1cs.num_wires())?);
+    // Public outputs, public inputs, private inputs.
+    header.extend_from_slice(&u32_of(r1cs.num_public)?);
+    header.extend_from_slice(&0u32.to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes());
+    header.extend_from_slice(&(r1cs.num_wires() as u64).to_le_bytes());
+    header.extend_from_slice(&u32_of(r1cs.constraints.len())?);
+
+    let mut constraints = vec![];
+    for constraint in &r1cs.constraints {
+        for lc in [&constraint.a, &constraint.b, &constraint.c].iter() {
+            constraints.extend_from_slice(&u32_of(lc.len())?);
+            for &(wire, coeff) in lc.iter() {
+                constraints.extend_from_slice(&u32_of(wire)?);
+                constraints.extend_from_slice(coeff.to_repr().as_ref());
+            }
+        }
+    }
+
+    let labels = (0..r1cs.num_wires() as u64)
+        .flat_map(u64::to_le_bytes)
+        .collect::<Vec<_>>();
+
+    writer.write_all(R1CS_MAGIC)?;
+    writer.write_all(&R1CS_VERSION.to_le_bytes())?;
+    writer.write_all(&3u32.to_le_bytes())?;
+    write_section(&mut writer, R1CS_HEADER, &header)?;
+    write_section(&mut writer, R1CS_CONSTRAINTS, &constraints)?;
+    write_section(&mut writer, R1CS_WIRE_TO_LABEL, &labels)
+}
+
+/// Writes the assignment of every wire in the iden3 `.wtns` format.
+pub fn write_wtns<Scalar: PrimeField, W: Write>(witness: &[Scalar], mut writer: W) -> Result<()> {
+    let prime = modulus::<Scalar>();
+
+    let mut header = vec![];
+    header.extend_from_slice(&u32_of(prime.len())?);
+    header.extend_from_slice(&prime);
+    header.extend_from_slice(&u32_of(witness.len())?);
+
+    let data = witness
+        .iter()
+        .flat_map(|value| value.to_repr().as_ref().to_vec())
+        .collect::<Vec<_>>();
+
+    writer.write_all(WTNS_MAGIC)?;
+    writer.write_all(&WTNS_VERSION.to_le_bytes())?;
+    writer.write_all(&2u32.to_le_bytes())?;
+    write_section(&mut writer, WTNS_HEADER, &header)?;
+    write_section(&mut writer, WTNS_DATA, &data)
+}
+
+/// Reads a constraint system in the iden3 `.r1cs` format. Public outputs and
+/// public inputs both become public inputs, in that order; every other wire
+/// is private. Labels are ignored.
+pub fn read_r1cs<Scalar: PrimeField, R: Read>(reader: R) -> Result<R1cs<Scalar>> {
+    let sections = read_sections(reader, R1CS_MAGIC, R1CS_VERSION)?;
+    for &(kind, _) in &sections {
+        if kind > R1CS_WIRE_TO_LABEL {
+            return Err(invalid(format!("unsupported section {}", kind)));
+        }
+    }
+
+    let mut header = section(&sections, R1CS_HEADER)?;
+    header.field::<Scalar>()?;
+    let num_wires = header.usize()?;
+    let num_public = header.usize()? + header.usize()?;
+    let _num_private_inputs = header.usize()?;
+    let _num_labels = header.u64()?;
+    let num_constraints = header.usize()?;
+    header.finish()?;
+    if num_public >= num_wires {
+        return Err(invalid(format!(
+            "{} public wires out of {}",
+            num_public, num_wires
+        )));
+    }
+
+    let mut data = section(&sections, R1CS_CONSTRAINTS)?;
+    let mut lc = || -> Result<Vec<(usize, Scalar)>> {
+        let len = data.usize()?;
+        let mut terms = vec![];
+        for _ in 0..len {
+            let wire = data.usize()?;
+            if wire >= num_wires {
+                return Err(invalid(format!("wire {} is out of range", wire)));
+            }
+            terms.push((wire, data.scalar()?));
+        }
+        Ok(terms)
+    };
+    let mut constraints = vec![];
+    for _ in 0..num_constraints {
+        constraints.push(Constraint {
+            a: lc()?,
+            b: lc()?,
+            c: lc()?,
+        });
+    }
+    data.finish()?;
+
+    Ok(R1cs {
+        num_public,
+        num_private: num_wires - 1 - num_public,
+        constraints,
+    })
+}
+
+/// Reads the assignment of every wire from the iden3 `.wtns` format.
+pub fn read_wtns<Scalar: PrimeField, R: Read>(reader: R) -> Result<Vec<Scalar>> {
+    let sections = read_sections(reader, WTNS_MAGIC, WTNS_VERSION)?;
+
+    let mut header = section(&sections, WTNS_HEADER)?;
+    header.field::<Scalar>()?;
+    let len = header.usize()?;
+    header.finish()?;
+
+    let mut data = section(&sections, WTNS_DATA)?;
+    if data.0.len() / modulus::<Scalar>().len() != len {
+        return Err(invalid(format!("expected {} values", len)));
+    }
+    let witness = (0..len)
+        .map(|_| data.scalar())
+        .collect::<Result<Vec<_>>>()?;
+    data.finish()?;
+    Ok(witness)
+}
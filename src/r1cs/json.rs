@@ -0,0 +1,60 @@
+//! JSON layout of `snarkjs r1cs export json`. Field elements are decimal
+//! strings.
+
+use std::collections::BTreeMap;
+
+use ff::PrimeField;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{modulus, R1cs};
+use crate::decimal::le_to_decimal;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonR1cs {
+    n8: usize,
+    prime: String,
+    n_vars: usize,
+    n_outputs: usize,
+    n_pub_inputs: usize,
+    n_prv_inputs: usize,
+    n_labels: usize,
+    n_constraints: usize,
+    /// Every constraint as `[A, B, C]`, each mapping a wire to a coefficient.
+    constraints: Vec<[BTreeMap<usize, String>; 3]>,
+    map: Vec<usize>,
+}
+
+/// Decimal string of a field element.
+fn to_decimal<Scalar: PrimeField>(value: &Scalar) -> String {
+    le_to_decimal(value.to_repr().as_ref())
+}
+
+/// Serializes `r1cs` in the layout of `snarkjs r1cs export json`.
+pub fn to_json<Scalar: PrimeField>(r1cs: &R1cs<Scalar>) -> Value {
+    let prime = modulus::<Scalar>();
+    let lc = |lc: &[(usize, Scalar)]| {
+        lc.iter()
+            .map(|(wire, coeff)| (*wire, to_decimal(coeff)))
+            .collect()
+    };
+
+    let json = JsonR1cs {
+        n8: prime.len(),
+        prime: le_to_decimal(&prime),
+        n_vars: r1cs.num_wires(),
+        n_outputs: r1cs.num_public,
+        n_pub_inputs: 0,
+        n_prv_inputs: 0,
+        n_labels: r1cs.num_wires(),
+        n_constraints: r1cs.constraints.len(),
+        constraints: r1cs
+            .constraints
+            .iter()
+            .map(|c| [lc(&c.a), lc(&c.b), lc(&c.c)])
+            .collect(),
+        map: (0..r1cs.num_wires()).collect(),
+    };
+    serde_json::to_value(json).expect("r1cs always serializes")
+}
@@ -0,0 +1,104 @@
+//! Rank-1 constraint systems in a tool-independent form, with import and
+//! export in the iden3 formats used by circom and snarkjs.
+//!
+//! Variables are numbered as iden3 wires: wire 0 is the constant "one",
+//! followed by the public inputs and then by every private variable. Public
+//! inputs of bellman circuits are declared as circuit outputs, which is how
+//! circom lays out the public signals it computes.
+
+mod circuit;
+mod iden3;
+mod json;
+
+pub use circuit::R1csCircuit;
+pub use iden3::{read_r1cs, read_wtns, write_r1cs, write_wtns};
+pub use json::to_json;
+
+use ff::PrimeField;
+
+use crate::{Error, Result};
+
+/// One constraint `A * B = C`, every linear combination as a list of
+/// `(wire, coefficient)` pairs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constraint<Scalar: PrimeField> {
+    pub a: Vec<(usize, Scalar)>,
+    pub b: Vec<(usize, Scalar)>,
+    pub c: Vec<(usize, Scalar)>,
+}
+
+/// A rank-1 constraint system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct R1cs<Scalar: PrimeField> {
+    /// Number of public inputs, not counting "one".
+    pub num_public: usize,
+    /// Number of private (auxiliary) variables.
+    pub num_private: usize,
+    pub constraints: Vec<Constraint<Scalar>>,
+}
+
+impl<Scalar: PrimeField> R1cs<Scalar> {
+    /// Total number of wires, including "one".
+    pub fn num_wires(&self) -> usize {
+        1 + self.num_public + self.num_private
+    }
+
+    /// Fails if a constraint refers to a wire past [`num_wires`](Self::num_wires).
+    pub fn check(&self) -> Result<()> {
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            let mut wires = constraint
+                .a
+                .iter()
+                .chain(&constraint.b)
+                .chain(&constraint.c);
+            if let Some(&(wire, _)) = wires.find(|&&(wire, _)| wire >= self.num_wires()) {
+                return Err(Error::Decode(format!(
+                    "constraint {}: wire {} is out of range",
+                    i, wire
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Index of the first unsatisfied constraint, if any, given the
+    /// assignment of every wire.
+    pub fn which_is_unsatisfied(&self, witness: &[Scalar]) -> Result<Option<usize>> {
+        self.check()?;
+        if witness.len() != self.num_wires() {
+            return Err(Error::length(
+                "witness values",
+                self.num_wires(),
+                witness.len(),
+            ));
+        }
+
+        let eval = |lc: &[(usize, Scalar)]| {
+            lc.iter().fold(Scalar::zero(), |acc, &(wire, coeff)| {
+                acc + witness[wire] * coeff
+            })
+        };
+        Ok(self
+            .constraints
+            .iter()
+            .position(|c| eval(&c.a) * eval(&c.b) != eval(&c.c)))
+    }
+
+    pub fn is_satisfied(&self, witness: &[Scalar]) -> Result<bool> {
+        Ok(self.which_is_unsatisfied(witness)?.is_none())
+    }
+}
+
+/// Little-endian encoding of the field modulus.
+fn modulus<Scalar: PrimeField>() -> Vec<u8> {
+    // -1 is the largest element, so the modulus is its encoding plus one.
+    let mut bytes = Scalar::one().neg().to_repr().as_ref().to_vec();
+    for byte in bytes.iter_mut() {
+        let (sum, carry) = byte.overflowing_add(1);
+        *byte = sum;
+        if !carry {
+            break;
+        }
+    }
+    bytes
+}
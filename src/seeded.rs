@@ -0,0 +1,51 @@
+//! **INSECURE** seeded randomness for reproducible setups and proofs.
+//!
+//! Groth16 is only sound if the randomness of the setup is thrown away, and
+//! only zero-knowledge if the randomness of each proof is secret. With a
+//! known seed, anyone can recompute the toxic waste of the setup and forge
+//! proofs, or strip the blinding from a proof and learn about the preimage.
+//! This module exists for known-answer tests and debugging, and is only
+//! compiled with the `insecure-seeded-rng` feature, which production builds
+//! must leave off.
+
+use std::convert::TryInto;
+
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+use crate::{Error, Result};
+
+/// The RNG of the seeded mode.
+pub type InsecureRng = ChaCha20Rng;
+
+/// Size of a seed in bytes.
+pub const SEED_SIZE: usize = 32;
+
+/// ChaCha20 RNG seeded with `seed`, usable in place of `OsRng` with
+/// `groth16::generate_random_parameters` and `groth16::create_random_proof`.
+pub fn insecure_rng(seed: [u8; SEED_SIZE]) -> InsecureRng {
+    ChaCha20Rng::from_seed(seed)
+}
+
+/// Decodes a seed from a hex string of [`SEED_SIZE`] bytes.
+pub fn insecure_seed_from_hex(seed: &str) -> Result<[u8; SEED_SIZE]> {
+    let seed = hex::decode(seed).map_err(|e| Error::Decode(format!("bad seed: {}", e)))?;
+    let len = seed.len();
+    seed.try_into()
+        .map_err(|_| Error::length("seed bytes", SEED_SIZE, len))
+}
+
+/// Like [`insecure_rng`], with the seed as a hex string of [`SEED_SIZE`]
+/// bytes.
+pub fn insecure_rng_from_hex(seed: &str) -> Result<InsecureRng> {
+    Ok(insecure_rng(insecure_seed_from_hex(seed)?))
+}
+
+/// RNG of proof `index` of a batch, for
+/// [`prove_batch`](crate::batch::prove_batch): stream `index` of the ChaCha20
+/// RNG seeded with `seed`. Proof 0 gets the same randomness as a single proof
+/// with [`insecure_rng`].
+pub fn insecure_batch_rng(seed: [u8; SEED_SIZE], index: usize) -> InsecureRng {
+    let mut rng = insecure_rng(seed);
+    rng.set_stream(index as u64);
+    rng
+}
@@ -0,0 +1,224 @@
+//! JSON files of snarkjs for Groth16 over BLS12-381: `proof.json`,
+//! `verification_key.json` and `public.json`.
+//!
+//! Every number is a decimal string and points are in projective
+//! coordinates, with `z = 1` for affine points and `z = 0` for the identity.
+//! A G2 coordinate is an `[c0, c1]` pair.
+//!
+//! snarkjs verifying keys omit two points of bellman's, `beta_g1` and
+//! `delta_g1`, which are only needed to prove: they decode as the identity.
+//! They also have a `vk_alphabeta_12` precomputed pairing, which snarkjs
+//! doesn't need to verify and `bls12_381` has no way to encode, so it is
+//! neither written nor read.
+
+use bellman::groth16::{Proof, VerifyingKey};
+use bls12_381::{Bls12, G1Affine, G2Affine, Scalar};
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decimal::{decimal_to_le, le_to_decimal},
+    Error, Result,
+};
+
+const PROTOCOL: &str = "groth16";
+const CURVE: &str = "bls12381";
+
+type JsonG1 = [String; 3];
+type JsonG2 = [[String; 2]; 3];
+
+#[derive(Serialize, Deserialize)]
+struct JsonProof {
+    pi_a: JsonG1,
+    pi_b: JsonG2,
+    pi_c: JsonG1,
+    protocol: String,
+    curve: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonVerifyingKey {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: usize,
+    vk_alpha_1: JsonG1,
+    vk_beta_2: JsonG2,
+    vk_gamma_2: JsonG2,
+    vk_delta_2: JsonG2,
+    #[serde(rename = "IC")]
+    ic: Vec<JsonG1>,
+}
+
+fn invalid<E: ToString>(e: E) -> Error {
+    Error::Decode(e.to_string())
+}
+
+fn check_header(protocol: &str, curve: &str) -> Result<()> {
+    if protocol != PROTOCOL {
+        return Err(invalid(format!("unsupported protocol {:?}", protocol)));
+    }
+    if curve != CURVE {
+        return Err(invalid(format!("unsupported curve {:?}", curve)));
+    }
+    Ok(())
+}
+
+/// Decimal string of a big-endian base field element.
+fn fp_to_decimal(be: &[u8]) -> String {
+    let mut le = be.to_vec();
+    le.reverse();
+    le_to_decimal(&le)
+}
+
+/// Big-endian encoding of a decimal base field element. Whether it is
+/// canonical is left to the point decoding.
+fn fp_from_decimal(s: &str) -> Result<[u8; 48]> {
+    let le = decimal_to_le(s, 48).ok_or_else(|| invalid(format!("{:?} is not a coordinate", s)))?;
+    let mut be = [0; 48];
+    for (a, b) in be.iter_mut().zip(le.iter().rev()) {
+        *a = *b;
+    }
+    Ok(be)
+}
+
+fn g1_to_json(p: &G1Affine) -> JsonG1 {
+    if bool::from(p.is_identity()) {
+        return ["0".to_string(), "1".to_string(), "0".to_string()];
+    }
+    let bytes = p.to_uncompressed();
+    [
+        fp_to_decimal(&bytes[..48]),
+        fp_to_decimal(&bytes[48..]),
+        "1".to_string(),
+    ]
+}
+
+fn g1_from_json(json: &JsonG1) -> Result<G1Affine> {
+    match json[2].as_str() {
+        "0" => return Ok(G1Affine::identity()),
+        "1" => {}
+        z => return Err(invalid(format!("unsupported z coordinate {:?}", z))),
+    }
+    let mut bytes = [0; 96];
+    bytes[..48].copy_from_slice(&fp_from_decimal(&json[0])?);
+    bytes[48..].copy_from_slice(&fp_from_decimal(&json[1])?);
+    Option::from(G1Affine::from_uncompressed(&bytes)).ok_or_else(|| invalid("not a point of G1"))
+}
+
+fn g2_to_json(p: &G2Affine) -> JsonG2 {
+    let pair = |c0: &str, c1: &str| [c0.to_string(), c1.to_string()];
+    if bool::from(p.is_identity()) {
+        return [pair("0", "0"), pair("1", "0"), pair("0", "0")];
+    }
+    // The uncompressed encoding is x.c1, x.c0, y.c1, y.c0.
+    let bytes = p.to_uncompressed();
+    let fp = |i: usize| fp_to_decimal(&bytes[48 * i..48 * (i + 1)]);
+    [[fp(1), fp(0)], [fp(3), fp(2)], pair("1", "0")]
+}
+
+fn g2_from_json(json: &JsonG2) -> Result<G2Affine> {
+    match (json[2][0].as_str(), json[2][1].as_str()) {
+        ("0", "0") => return Ok(G2Affine::identity()),
+        ("1", "0") => {}
+        z => return Err(invalid(format!("unsupported z coordinate {:?}", z))),
+    }
+    let mut bytes = [0; 192];
+    for (i, s) in [&json[0][1], &json[0][0], &json[1][1], &json[1][0]]
+        .iter()
+        .enumerate()
+    {
+        bytes[48 * i..48 * (i + 1)].copy_from_slice(&fp_from_decimal(s)?);
+    }
+    Option::from(G2Affine::from_uncompressed(&bytes)).ok_or_else(|| invalid("not a point of G2"))
+}
+
+/// Encodes `proof` as a snarkjs `proof.json`.
+pub fn proof_to_json(proof: &Proof<Bls12>) -> String {
+    let json = JsonProof {
+        pi_a: g1_to_json(&proof.a),
+        pi_b: g2_to_json(&proof.b),
+        pi_c: g1_to_json(&proof.c),
+        protocol: PROTOCOL.to_string(),
+        curve: CURVE.to_string(),
+    };
+    serde_json::to_string_pretty(&json).expect("proof always serializes")
+}
+
+/// Decodes a snarkjs `proof.json`. Points are checked to be in the right
+/// subgroup.
+pub fn proof_from_json(s: &str) -> Result<Proof<Bls12>> {
+    let json: JsonProof = serde_json::from_str(s).map_err(invalid)?;
+    check_header(&json.protocol, &json.curve)?;
+    Ok(Proof {
+        a: g1_from_json(&json.pi_a)?,
+        b: g2_from_json(&json.pi_b)?,
+        c: g1_from_json(&json.pi_c)?,
+    })
+}
+
+/// Encodes `vk` as a snarkjs `verification_key.json`.
+pub fn verifying_key_to_json(vk: &VerifyingKey<Bls12>) -> String {
+    let json = JsonVerifyingKey {
+        protocol: PROTOCOL.to_string(),
+        curve: CURVE.to_string(),
+        n_public: vk.ic.len().saturating_sub(1),
+        vk_alpha_1: g1_to_json(&vk.alpha_g1),
+        vk_beta_2: g2_to_json(&vk.beta_g2),
+        vk_gamma_2: g2_to_json(&vk.gamma_g2),
+        vk_delta_2: g2_to_json(&vk.delta_g2),
+        ic: vk.ic.iter().map(g1_to_json).collect(),
+    };
+    serde_json::to_string_pretty(&json).expect("verifying key always serializes")
+}
+
+/// Decodes a snarkjs `verification_key.json`, with `beta_g1` and `delta_g1`
+/// set to the identity. The result only verifies proofs: its fingerprint
+/// isn't the one of the original key, and
+/// [`params::write_verifying_key`](crate::params::write_verifying_key)
+/// refuses to persist it.
+pub fn verifying_key_from_json(s: &str) -> Result<VerifyingKey<Bls12>> {
+    let json: JsonVerifyingKey = serde_json::from_str(s).map_err(invalid)?;
+    check_header(&json.protocol, &json.curve)?;
+    if json.n_public.checked_add(1) != Some(json.ic.len()) {
+        return Err(invalid(format!(
+            "{} IC points for {} public inputs",
+            json.ic.len(),
+            json.n_public
+        )));
+    }
+    Ok(VerifyingKey {
+        alpha_g1: g1_from_json(&json.vk_alpha_1)?,
+        beta_g1: G1Affine::identity(),
+        beta_g2: g2_from_json(&json.vk_beta_2)?,
+        gamma_g2: g2_from_json(&json.vk_gamma_2)?,
+        delta_g1: G1Affine::identity(),
+        delta_g2: g2_from_json(&json.vk_delta_2)?,
+        ic: json.ic.iter().map(g1_from_json).collect::<Result<_>>()?,
+    })
+}
+
+/// Encodes public inputs as a snarkjs `public.json`.
+pub fn public_inputs_to_json(inputs: &[Scalar]) -> String {
+    let json = inputs
+        .iter()
+        .map(|input| le_to_decimal(input.to_repr().as_ref()))
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&json).expect("public inputs always serialize")
+}
+
+/// Decodes a snarkjs `public.json`. Inputs must be reduced modulo the scalar
+/// field.
+pub fn public_inputs_from_json(s: &str) -> Result<Vec<Scalar>> {
+    let json: Vec<String> = serde_json::from_str(s).map_err(invalid)?;
+    json.iter()
+        .map(|s| {
+            let mut repr = [0; 32];
+            let le = decimal_to_le(s, 32)
+                .ok_or_else(|| invalid(format!("{:?} is not a field element", s)))?;
+            repr.copy_from_slice(&le);
+            Scalar::from_repr(repr)
+                .ok_or_else(|| invalid(format!("{:?} is not a field element", s)))
+        })
+        .collect()
+}
@@ -0,0 +1,216 @@
+//! Solidity verifier contract for a verifying key, built on the BLS12-381
+//! precompiles of EIP-2537, and the calldata to call it with.
+//!
+//! The contract checks the Groth16 equation as a single pairing check,
+//!
+//! ```text
+//! e(A, B) * e(alpha, -beta) * e(L, -gamma) * e(C, -delta) == 1
+//! ```
+//!
+//! where `L` is the combination of the `IC` points with the public inputs.
+//! The G2 points of the key are negated here, so that the contract doesn't
+//! need any field arithmetic of its own.
+//!
+//! In EIP-2537, a base field element is encoded in 64 big-endian bytes, an
+//! element of the quadratic extension as `c0 || c1`, a point as `x || y` and
+//! the identity as zeros.
+
+use std::fmt::Write;
+
+use bellman::groth16::{Proof, VerifyingKey};
+use bls12_381::{Bls12, G1Affine, G2Affine, Scalar};
+use ff::PrimeField;
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::{params::verifying_key_fingerprint, Error, Result};
+
+/// Name of the generated contract.
+pub const CONTRACT_NAME: &str = "XorSumVerifier";
+
+/// Size of a proof in the EIP-2537 encoding: A, B and C.
+pub const PROOF_SIZE: usize = 2 * 128 + 256;
+
+/// Pads a big-endian base field element to 64 bytes.
+fn pad_fp(out: &mut Vec<u8>, fp: &[u8]) {
+    out.extend_from_slice(&[0; 16]);
+    out.extend_from_slice(fp);
+}
+
+/// EIP-2537 encoding of a G1 point.
+pub fn encode_g1(p: &G1Affine) -> [u8; 128] {
+    let mut out = Vec::with_capacity(128);
+    if bool::from(p.is_identity()) {
+        out.resize(128, 0);
+    } else {
+        let bytes = p.to_uncompressed();
+        pad_fp(&mut out, &bytes[..48]);
+        pad_fp(&mut out, &bytes[48..]);
+    }
+    let mut encoded = [0; 128];
+    encoded.copy_from_slice(&out);
+    encoded
+}
+
+/// EIP-2537 encoding of a G2 point.
+pub fn encode_g2(p: &G2Affine) -> [u8; 256] {
+    let mut out = Vec::with_capacity(256);
+    if bool::from(p.is_identity()) {
+        out.resize(256, 0);
+    } else {
+        // bls12_381 puts c1 before c0.
+        let bytes = p.to_uncompressed();
+        for &i in &[1, 0, 3, 2] {
+            pad_fp(&mut out, &bytes[48 * i..48 * (i + 1)]);
+        }
+    }
+    let mut encoded = [0; 256];
+    encoded.copy_from_slice(&out);
+    encoded
+}
+
+/// EIP-2537 encoding of a proof, as the contract expects it.
+pub fn encode_proof(proof: &Proof<Bls12>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(PROOF_SIZE);
+    out.extend_from_slice(&encode_g1(&proof.a));
+    out.extend_from_slice(&encode_g2(&proof.b));
+    out.extend_from_slice(&encode_g1(&proof.c));
+    out
+}
+
+/// Big-endian encoding of a scalar as a `uint256`.
+fn encode_scalar(s: &Scalar) -> [u8; 32] {
+    let mut bytes = s.to_repr();
+    bytes.reverse();
+    bytes
+}
+
+/// Solidity signature of the verifying function for `num_inputs` inputs.
+pub fn signature(num_inputs: usize) -> String {
+    format!("verifyProof(bytes,uint256[{}])", num_inputs)
+}
+
+/// Function selector of the verifying function: the first 4 bytes of the
+/// Keccak-256 digest of its signature.
+pub fn selector(num_inputs: usize) -> [u8; 4] {
+    let mut keccak = Keccak::v256();
+    keccak.update(signature(num_inputs).as_bytes());
+    let mut digest = [0; 32];
+    keccak.finalize(&mut digest);
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+/// ABI-encoded call of `verifyProof(proof, inputs)`.
+pub fn calldata(proof: &Proof<Bls12>, inputs: &[Scalar]) -> Vec<u8> {
+    let mut out = selector(inputs.len()).to_vec();
+    // The fixed-size input array is inline, the proof bytes come after it.
+    out.extend_from_slice(&word(32 * (1 + inputs.len())));
+    for input in inputs {
+        out.extend_from_slice(&encode_scalar(input));
+    }
+    out.extend_from_slice(&word(PROOF_SIZE));
+    // The size of the proof is a multiple of 32, so it needs no padding.
+    out.extend_from_slice(&encode_proof(proof));
+    out
+}
+
+fn word(n: usize) -> [u8; 32] {
+    let mut word = [0; 32];
+    word[24..].copy_from_slice(&(n as u64).to_be_bytes());
+    word
+}
+
+/// Source of a standalone contract that verifies proofs for `vk`.
+pub fn verifier_contract(vk: &VerifyingKey<Bls12>) -> Result<String> {
+    let num_inputs = vk.ic.len().checked_sub(1).ok_or(Error::InvalidLength {
+        what: "IC points",
+        min: 1,
+        max: usize::MAX,
+        got: 0,
+    })?;
+    let mut modulus = (-Scalar::one()).to_repr();
+    modulus.reverse();
+    // -1 ends with 0x00, so adding one doesn't carry.
+    modulus[31] += 1;
+
+    let mut constants = String::new();
+    let mut constant = |name: String, bytes: &[u8]| {
+        writeln!(constants, "    bytes constant {} =", name).unwrap();
+        for chunk in bytes.chunks(64) {
+            writeln!(constants, "        hex\"{}\"", hex::encode(chunk)).unwrap();
+        }
+        constants.pop();
+        constants.push_str(";\n");
+    };
+    constant("ALPHA".to_string(), &encode_g1(&vk.alpha_g1));
+    constant("NEG_BETA".to_string(), &encode_g2(&-vk.beta_g2));
+    constant("NEG_GAMMA".to_string(), &encode_g2(&-vk.gamma_g2));
+    constant("NEG_DELTA".to_string(), &encode_g2(&-vk.delta_g2));
+    for (i, ic) in vk.ic.iter().enumerate() {
+        constant(format!("IC{}", i), &encode_g1(ic));
+    }
+
+    let mut msm = "            IC0, uint256(1)".to_string();
+    for i in 0..num_inputs {
+        write!(msm, ",\n            IC{}, input[{}]", i + 1, i).unwrap();
+    }
+
+    Ok(format!(
+        r#"// SPDX-License-Identifier: MIT
+// Generated by bellman-test for the verifying key with fingerprint
+// {fingerprint}.
+pragma solidity ^0.8.0;
+
+/// Verifies Groth16 proofs of knowledge of a xor-sum preimage, with the
+/// BLS12-381 precompiles of EIP-2537.
+contract {name} {{
+    /// Order of the scalar field, public inputs must be below it.
+    uint256 constant R = 0x{modulus};
+
+    address constant G1_MSM = address(0x0c);
+    address constant PAIRING_CHECK = address(0x0f);
+
+    // The verifying key, with the G2 points negated.
+{constants}
+    /// Checks `proof`, the points A, B and C in the EIP-2537 encoding,
+    /// against the public inputs.
+    function verifyProof(bytes calldata proof, uint256[{num_inputs}] calldata input)
+        external
+        view
+        returns (bool)
+    {{
+        if (proof.length != {proof_size}) {{
+            return false;
+        }}
+        for (uint256 i = 0; i < {num_inputs}; i++) {{
+            if (input[i] >= R) {{
+                return false;
+            }}
+        }}
+
+        (bool ok, bytes memory l) = G1_MSM.staticcall(abi.encodePacked(
+{msm}
+        ));
+        if (!ok) {{
+            return false;
+        }}
+
+        bytes memory result;
+        (ok, result) = PAIRING_CHECK.staticcall(abi.encodePacked(
+            proof[0:128], proof[128:384],
+            ALPHA, NEG_BETA,
+            l, NEG_GAMMA,
+            proof[384:512], NEG_DELTA
+        ));
+        return ok && result.length == 32 && abi.decode(result, (uint256)) == 1;
+    }}
+}}
+"#,
+        fingerprint = hex::encode(verifying_key_fingerprint(vk)),
+        name = CONTRACT_NAME,
+        modulus = hex::encode(modulus),
+        constants = constants,
+        num_inputs = num_inputs,
+        proof_size = PROOF_SIZE,
+        msm = msm,
+    ))
+}
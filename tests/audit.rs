@@ -0,0 +1,127 @@
+use bellman::{
+    gadgets::{
+        boolean::{AllocatedBit, Boolean},
+        multipack,
+    },
+    ConstraintSystem, SynthesisError,
+};
+use bellman_test::{
+    cs::{audit, AuditReport, Auditor},
+    xor_sum, CircuitConfig, MyCircuit, XorGadget,
+};
+use bls12_381::Scalar;
+
+/// Audits whatever `synthesize` allocates and enforces.
+fn audit_with<F>(synthesize: F) -> AuditReport
+where
+    F: FnOnce(&mut Auditor<Scalar>) -> Result<(), SynthesisError>,
+{
+    let mut cs = Auditor::new();
+    synthesize(&mut cs).unwrap();
+    cs.report()
+}
+
+#[test]
+fn my_circuit_is_clean() {
+    for &variable_length in &[false, true] {
+        for &output_size in &[1, 5, 40] {
+            for gadget in XorGadget::ALL.iter().copied() {
+                let config = CircuitConfig {
+                    variable_length,
+                    output_size,
+                    gadget,
+                    ..CircuitConfig::default()
+                };
+                let report = audit::<Scalar, _>(MyCircuit::new(config)).unwrap();
+                assert!(report.is_clean(), "{:?}:\n{}", config, report);
+            }
+        }
+    }
+}
+
+#[test]
+fn unconstrained_variable() {
+    let report = audit_with(|cs| {
+        let bit = AllocatedBit::alloc(cs.namespace(|| "bit"), None)?;
+        cs.alloc(|| "forgotten", || Ok(Scalar::one()))?;
+        multipack::pack_into_inputs(cs.namespace(|| "pack"), &[Boolean::from(bit)])
+    });
+
+    assert_eq!(report.unconstrained, ["forgotten"]);
+    assert!(report.disconnected.is_empty());
+    assert!(report.unchecked_booleans.is_empty());
+}
+
+#[test]
+fn hash_not_exposed() {
+    // The xor-sum is computed, but never packed into the public inputs.
+    let report = audit_with(|cs| {
+        let bits = (0..16)
+            .map(|i| {
+                AllocatedBit::alloc(cs.namespace(|| format!("bit {}", i)), None).map(Boolean::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        xor_sum(cs.namespace(|| "xor_sum"), &bits, 2)?;
+        Ok(())
+    });
+
+    assert!(report.unconstrained.is_empty());
+    // 16 input bits and 8 xor results.
+    assert_eq!(report.disconnected.len(), 24);
+    assert!(report.disconnected.contains(&"bit 3/boolean".to_string()));
+    assert!(report
+        .disconnected
+        .contains(&"xor_sum/xor [1][7]/xor result".to_string()));
+}
+
+#[test]
+fn missing_boolean_constraint() {
+    let report = audit_with(|cs| {
+        // A hand-rolled bit, that is packed into the input but never checked
+        // to be 0 or 1.
+        let bit = cs.alloc(|| "boolean", || Ok(Scalar::one()))?;
+        let input = cs.alloc_input(|| "input", || Ok(Scalar::one()))?;
+        cs.enforce(
+            || "pack",
+            |lc| lc + bit,
+            |lc| lc + Auditor::<Scalar>::one(),
+            |lc| lc + input,
+        );
+
+        let hand_rolled = cs.alloc(|| "flag", || Ok(Scalar::one()))?;
+        cs.enforce(
+            || "flag is input",
+            |lc| lc + hand_rolled,
+            |lc| lc + Auditor::<Scalar>::one(),
+            |lc| lc + input,
+        );
+        Ok(())
+    });
+
+    assert!(report.unconstrained.is_empty());
+    assert!(report.disconnected.is_empty());
+    assert_eq!(report.unchecked_booleans, ["boolean"]);
+}
+
+#[test]
+fn custom_boolean_names() {
+    let mut cs = Auditor::<Scalar>::new().with_boolean_name("flag");
+    let flag = cs.alloc(|| "flag", || Ok(Scalar::one())).unwrap();
+    let input = cs.alloc_input(|| "input", || Ok(Scalar::one())).unwrap();
+    cs.enforce(
+        || "flag is input",
+        |lc| lc + flag,
+        |lc| lc + Auditor::<Scalar>::one(),
+        |lc| lc + input,
+    );
+    assert_eq!(cs.report().unchecked_booleans, ["flag"]);
+
+    // Either order of the factors counts as a boolean constraint.
+    cs.enforce(
+        || "flag is boolean",
+        |lc| lc + flag,
+        |lc| lc + Auditor::<Scalar>::one() - flag,
+        |lc| lc,
+    );
+    assert!(cs.report().is_clean());
+}
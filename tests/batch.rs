@@ -0,0 +1,124 @@
+use bellman::groth16::{self, Parameters, Proof};
+use bellman_test::{
+    batch::{prove_batch, BatchVerifier},
+    native_xor_fold,
+    proof::ProofBundle,
+    CircuitConfig, Error, MyCircuit,
+};
+use bls12_381::{Bls12, Scalar};
+use rand::rngs::OsRng;
+
+const PREIMAGES: [&[u8; 32]; 6] = [
+    b"da confusion of da highest orda!",
+    b"abcdefghijklmnopqrstuvwxyz012345",
+    b"00000000000000000000000000000000",
+    b"the quick brown fox jumps over t",
+    b"he lazy dog, again and again and",
+    b"\xff\xfe\xfd\xfc\xfb\xfa\xf9\xf8\xf7\xf6\xf5\xf4\xf3\xf2\xf1\xf0\
+      \xef\xee\xed\xec\xeb\xea\xe9\xe8\xe7\xe6\xe5\xe4\xe3\xe2\xe1\xe0",
+];
+
+type Batch = Vec<(Proof<Bls12>, Vec<Scalar>)>;
+
+fn setup() -> (Parameters<Bls12>, Batch) {
+    let config = CircuitConfig {
+        output_size: 2,
+        ..CircuitConfig::default()
+    };
+    let params =
+        groth16::generate_random_parameters::<Bls12, _, _>(MyCircuit::new(config), &mut OsRng)
+            .unwrap();
+    let proofs = PREIMAGES
+        .iter()
+        .map(|preimage| {
+            let proof = groth16::create_random_proof(
+                MyCircuit::with_preimage(config, preimage.to_vec()),
+                &params,
+                &mut OsRng,
+            )
+            .unwrap();
+            let hash = native_xor_fold(&preimage[..], config.output_size).unwrap();
+            let inputs = ProofBundle::new(proof.clone(), hash, &params.vk)
+                .public_inputs(config.output_size)
+                .unwrap();
+            (proof, inputs)
+        })
+        .collect();
+    (params, proofs)
+}
+
+#[test]
+fn batch_verification() {
+    let (params, proofs) = setup();
+    let verifier = BatchVerifier::new(&params.vk);
+
+    assert!(verifier.verify(&proofs, &mut OsRng).is_ok());
+    assert!(verifier.verify(&[], &mut OsRng).is_ok());
+
+    // A wrong public input, a proof for another statement, and a proof with
+    // too many inputs.
+    let mut invalid = proofs.clone();
+    invalid[1].1[0] += Scalar::one();
+    invalid[4].0 = invalid[0].0.clone();
+    invalid[5].1.push(Scalar::zero());
+    assert!(matches!(
+        verifier.verify(&invalid, &mut OsRng),
+        Err(Error::InvalidProofs(invalid)) if invalid == [1, 4, 5]
+    ));
+    for (i, (proof, inputs)) in invalid.iter().enumerate() {
+        let valid = groth16::verify_proof(verifier.prepared_verifying_key(), proof, inputs).is_ok();
+        assert_eq!(valid, ![1, 4, 5].contains(&i));
+    }
+
+    // Two invalid proofs whose errors cancel out when the equations are
+    // added up without random weights.
+    let mut swapped = proofs;
+    let inputs = swapped[2].1.clone();
+    swapped[2].1 = swapped[3].1.clone();
+    swapped[3].1 = inputs;
+    assert!(matches!(
+        verifier.verify(&swapped, &mut OsRng),
+        Err(Error::InvalidProofs(invalid)) if invalid == [2, 3]
+    ));
+}
+
+#[test]
+fn batch_proving_keeps_order() {
+    let config = CircuitConfig {
+        input_size: 4,
+        ..CircuitConfig::default()
+    };
+    let params =
+        groth16::generate_random_parameters::<Bls12, _, _>(MyCircuit::new(config), &mut OsRng)
+            .unwrap();
+    let preimages = (0..5u8).map(|i| vec![i; 4]).collect::<Vec<_>>();
+
+    let mut reported = vec![];
+    let proofs = prove_batch(
+        &params,
+        config,
+        &preimages,
+        3,
+        |_| OsRng,
+        |i, result| {
+            assert!(result.is_ok());
+            reported.push(i);
+        },
+    );
+    reported.sort_unstable();
+    assert_eq!(reported, vec![0, 1, 2, 3, 4]);
+
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+    for (preimage, proof) in preimages.iter().zip(proofs) {
+        let hash = native_xor_fold(preimage, 1).unwrap();
+        let bundle = ProofBundle::new(proof.unwrap(), hash, &params.vk);
+        assert!(bundle.verify(&pvk, config.output_size).is_ok());
+    }
+
+    // More threads than preimages, and none at all.
+    assert_eq!(
+        prove_batch(&params, config, &preimages[..1], 8, |_| OsRng, |_, _| ()).len(),
+        1
+    );
+    assert!(prove_batch(&params, config, &[], 2, |_| OsRng, |_, _| ()).is_empty());
+}
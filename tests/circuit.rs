@@ -0,0 +1,209 @@
+//! Runs the circuit and its gadgets under bellman's `TestConstraintSystem`,
+//! which also panics if two objects are created at the same namespace path.
+
+use bellman::{
+    gadgets::{
+        boolean::{AllocatedBit, Boolean},
+        multipack,
+        test::TestConstraintSystem,
+    },
+    Circuit, ConstraintSystem,
+};
+use bellman_test::{
+    native_xor_fold, native_xor_sum, xor_fold, xor_fold_lc, xor_sum, CircuitConfig, MyCircuit,
+    XorGadget,
+};
+use bls12_381::Scalar;
+
+const PREIMAGE: &[u8; 32] = b"da confusion of da highest orda!";
+
+fn alloc_bits(cs: &mut TestConstraintSystem<Scalar>, data: &[u8]) -> Vec<Boolean> {
+    multipack::bytes_to_bits_le(data)
+        .into_iter()
+        .enumerate()
+        .map(|(i, b)| {
+            AllocatedBit::alloc(cs.namespace(|| format!("bit {}", i)), Some(b))
+                .map(Boolean::from)
+                .unwrap()
+        })
+        .collect()
+}
+
+fn bits_to_bytes(bits: &[Boolean]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|byte| {
+            byte.iter()
+                .enumerate()
+                .map(|(i, b)| (b.get_value().unwrap() as u8) << i)
+                .sum()
+        })
+        .collect()
+}
+
+fn synthesize(config: CircuitConfig, preimage: &[u8]) -> TestConstraintSystem<Scalar> {
+    let mut cs = TestConstraintSystem::new();
+    MyCircuit::with_preimage(config, preimage.to_vec())
+        .synthesize(&mut cs)
+        .unwrap();
+    cs
+}
+
+fn expected_inputs(hash: &[u8]) -> Vec<Scalar> {
+    multipack::compute_multipacking(&multipack::bytes_to_bits_le(hash))
+}
+
+#[test]
+fn xor_sum_matches_native() {
+    for data in [[0u8; 32], [0xff; 32], *PREIMAGE].iter() {
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let bits = alloc_bits(&mut cs, data);
+        let hash = xor_sum(cs.namespace(|| "xor_sum"), &bits, 32).unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_eq!(bits_to_bytes(&hash), [native_xor_sum(data)]);
+    }
+}
+
+#[test]
+fn xor_sum_constraint_count() {
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let bits = alloc_bits(&mut cs, PREIMAGE);
+    let before = cs.num_constraints();
+    xor_sum(cs.namespace(|| "xor_sum"), &bits, 32).unwrap();
+
+    // The first byte is XORed with constants for free, every other bit costs
+    // one constraint.
+    assert_eq!(cs.num_constraints() - before, 31 * 8);
+}
+
+#[test]
+fn xor_sum_namespaces_are_unique() {
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let bits = alloc_bits(&mut cs, PREIMAGE);
+    xor_sum(cs.namespace(|| "xor_sum"), &bits, 32).unwrap();
+
+    // Every XOR of every bit gets its own namespace.
+    for i in 1..32 {
+        for j in 0..8 {
+            let path = format!("xor_sum/xor [{}][{}]/xor result", i, j);
+            cs.get(&path);
+        }
+    }
+}
+
+#[test]
+fn xor_fold_gadgets_match_native() {
+    for &output_size in &[1, 3, 4, 8, 32, 40] {
+        let expected = native_xor_fold(PREIMAGE, output_size).unwrap();
+
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let bits = alloc_bits(&mut cs, PREIMAGE);
+        let boolean = xor_fold(cs.namespace(|| "boolean"), &bits, 32, output_size).unwrap();
+        let linear = xor_fold_lc(cs.namespace(|| "linear"), &bits, 32, output_size).unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_eq!(bits_to_bytes(&boolean), expected);
+        assert_eq!(bits_to_bytes(&linear), expected);
+    }
+}
+
+#[test]
+fn circuit_is_satisfied() {
+    let config = CircuitConfig::default();
+    let cs = synthesize(config, PREIMAGE);
+
+    assert!(cs.is_satisfied());
+    // 256 boolean constraints for the preimage bits, 248 XORs and one to pack
+    // the hash.
+    assert_eq!(cs.num_constraints(), 256 + 248 + 1);
+    assert_eq!(
+        cs.num_constraints(),
+        config.shape().unwrap().num_constraints()
+    );
+    assert_eq!(cs.num_inputs(), 2);
+    assert!(cs.verify(&expected_inputs(&[native_xor_sum(PREIMAGE)])));
+}
+
+#[test]
+fn circuit_public_input() {
+    let mut cs = synthesize(CircuitConfig::default(), PREIMAGE);
+
+    let hash = native_xor_sum(PREIMAGE);
+    assert_eq!(
+        cs.get_input(1, "pack hash/input 0"),
+        Scalar::from(u64::from(hash))
+    );
+}
+
+#[test]
+fn linear_gadget_is_cheaper() {
+    let boolean = synthesize(CircuitConfig::default(), PREIMAGE);
+    let linear = synthesize(
+        CircuitConfig {
+            gadget: XorGadget::Linear,
+            ..CircuitConfig::default()
+        },
+        PREIMAGE,
+    );
+
+    assert!(linear.is_satisfied());
+    assert!(linear.verify(&expected_inputs(&[native_xor_sum(PREIMAGE)])));
+    // Each of the 8 output bits sums 32 input bits: one parity bit, five
+    // quotient bits and the decomposition itself.
+    assert_eq!(linear.num_constraints(), 256 + 8 * 7 + 1);
+    assert!(linear.num_constraints() < boolean.num_constraints());
+}
+
+#[test]
+fn wide_digest() {
+    for gadget in XorGadget::ALL.iter().copied() {
+        let config = CircuitConfig {
+            output_size: 8,
+            gadget,
+            ..CircuitConfig::default()
+        };
+        let cs = synthesize(config, PREIMAGE);
+
+        assert!(cs.is_satisfied());
+        assert!(cs.verify(&expected_inputs(&native_xor_fold(PREIMAGE, 8).unwrap())));
+    }
+}
+
+#[test]
+fn variable_length() {
+    for gadget in XorGadget::ALL.iter().copied() {
+        let config = CircuitConfig {
+            variable_length: true,
+            output_size: 2,
+            gadget,
+            ..CircuitConfig::default()
+        };
+
+        for &length in &[0, 1, 5, 31, 32] {
+            let preimage = &PREIMAGE[..length];
+            let cs = synthesize(config, preimage);
+
+            assert!(cs.is_satisfied(), "length {}", length);
+            assert!(cs.verify(&expected_inputs(&native_xor_fold(preimage, 2).unwrap())));
+            assert_eq!(
+                cs.num_constraints(),
+                config.shape().unwrap().num_constraints()
+            );
+        }
+    }
+}
+
+#[test]
+fn padding_does_not_affect_hash() {
+    let config = CircuitConfig {
+        variable_length: true,
+        ..CircuitConfig::default()
+    };
+    let mut cs = synthesize(config, &PREIMAGE[..10]);
+
+    // Set a preimage bit past the length: it is masked out, so the circuit is
+    // still satisfied with the same public input.
+    cs.set("preimage bit 100/boolean", Scalar::one());
+    assert!(cs.is_satisfied());
+    assert!(cs.verify(&expected_inputs(&[native_xor_sum(&PREIMAGE[..10])])));
+}
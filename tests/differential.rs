@@ -0,0 +1,94 @@
+//! Differential tests of the circuit against the native hash on random
+//! preimages and configurations.
+
+use bellman::{
+    gadgets::{multipack, test::TestConstraintSystem},
+    Circuit,
+};
+use bellman_test::{native_xor_fold, CircuitConfig, MyCircuit, XorGadget};
+use bls12_381::Scalar;
+use ff::Field;
+use proptest::prelude::*;
+
+fn config() -> impl Strategy<Value = CircuitConfig> {
+    (1usize..48, any::<bool>(), 1usize..40, any::<bool>()).prop_map(
+        |(input_size, variable_length, output_size, linear)| CircuitConfig {
+            input_size,
+            variable_length,
+            output_size,
+            gadget: if linear {
+                XorGadget::Linear
+            } else {
+                XorGadget::Boolean
+            },
+        },
+    )
+}
+
+/// A configuration with a preimage it accepts.
+fn config_and_preimage() -> impl Strategy<Value = (CircuitConfig, Vec<u8>)> {
+    config().prop_flat_map(|config| {
+        let length = if config.variable_length {
+            0..=config.input_size
+        } else {
+            config.input_size..=config.input_size
+        };
+        (Just(config), prop::collection::vec(any::<u8>(), length))
+    })
+}
+
+fn synthesize(config: CircuitConfig, preimage: &[u8]) -> TestConstraintSystem<Scalar> {
+    let mut cs = TestConstraintSystem::new();
+    MyCircuit::with_preimage(config, preimage.to_vec())
+        .synthesize(&mut cs)
+        .unwrap();
+    cs
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn public_input_is_native_hash((config, preimage) in config_and_preimage()) {
+        let cs = synthesize(config, &preimage);
+        let hash = native_xor_fold(&preimage, config.output_size).unwrap();
+        let expected = multipack::compute_multipacking(&multipack::bytes_to_bits_le(&hash));
+
+        prop_assert!(cs.is_satisfied());
+        prop_assert!(cs.verify(&expected));
+    }
+
+    #[test]
+    fn other_public_inputs_are_unsatisfiable(
+        (config, preimage) in config_and_preimage(),
+        index in any::<prop::sample::Index>(),
+        delta in 1u64..,
+    ) {
+        let mut cs = synthesize(config, &preimage);
+
+        // Move one of the packed inputs (the first one is the constant "one")
+        // to any other value.
+        let i = index.index(cs.num_inputs() - 1);
+        let path = format!("pack hash/input {}", i);
+        let mut value = cs.get_input(i + 1, &path);
+        value += Scalar::from(delta);
+        cs.set(&path, value);
+
+        prop_assert!(!cs.is_satisfied());
+        let packing = format!("pack hash/packing constraint {}", i);
+        prop_assert_eq!(cs.which_is_unsatisfied(), Some(packing.as_str()));
+    }
+
+    #[test]
+    fn negated_input_is_unsatisfiable((config, preimage) in config_and_preimage()) {
+        let mut cs = synthesize(config, &preimage);
+
+        // Field negation of a packed value is never itself, unless it's zero.
+        let mut value = cs.get_input(1, "pack hash/input 0");
+        prop_assume!(!value.is_zero());
+        value = value.neg();
+        cs.set("pack hash/input 0", value);
+
+        prop_assert!(!cs.is_satisfied());
+    }
+}
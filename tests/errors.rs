@@ -0,0 +1,127 @@
+//! Bad inputs are reported as errors instead of panics.
+
+use bellman::{gadgets::test::TestConstraintSystem, groth16, Circuit, SynthesisError};
+use bellman_test::{
+    native_xor_fold, params, proof::ProofBundle, xor_fold, CircuitConfig, Error, MyCircuit,
+    MAX_INPUT_SIZE, MAX_OUTPUT_SIZE,
+};
+use bls12_381::{Bls12, Scalar};
+use rand::rngs::OsRng;
+
+fn config() -> CircuitConfig {
+    CircuitConfig {
+        input_size: 4,
+        ..CircuitConfig::default()
+    }
+}
+
+#[test]
+fn bad_lengths() {
+    assert!(matches!(
+        native_xor_fold(b"abcd", 0),
+        Err(Error::InvalidLength { got: 0, .. })
+    ));
+
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    assert!(matches!(
+        xor_fold(&mut cs, &[], 4, 1),
+        Err(Error::InvalidLength {
+            min: 32,
+            max: 32,
+            got: 0,
+            ..
+        })
+    ));
+
+    let err = config().check_preimage(b"abc").unwrap_err();
+    assert_eq!(err.to_string(), "expected 4 preimage bytes, got 3");
+    let config = CircuitConfig {
+        variable_length: true,
+        ..config()
+    };
+    let err = config.check_preimage(b"abcde").unwrap_err();
+    assert_eq!(err.to_string(), "expected at most 4 preimage bytes, got 5");
+    assert!(config.check_preimage(b"abc").is_ok());
+
+    // Synthesis reports the bad length through bellman's error type.
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let c = MyCircuit::with_preimage(config, b"abcde".to_vec());
+    assert!(matches!(
+        c.synthesize(&mut cs),
+        Err(SynthesisError::IoError(_))
+    ));
+}
+
+#[test]
+fn proving_and_verification_errors() {
+    let config = config();
+    let params =
+        groth16::generate_random_parameters::<Bls12, _, _>(MyCircuit::new(config), &mut OsRng)
+            .unwrap();
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    assert!(matches!(
+        ProofBundle::create(&params, &config, b"abc".to_vec(), &mut OsRng),
+        Err(Error::InvalidLength { got: 3, .. })
+    ));
+
+    let mut bundle = ProofBundle::create(&params, &config, b"abcd".to_vec(), &mut OsRng).unwrap();
+    assert!(bundle.verify(&pvk, config.output_size).is_ok());
+    bundle.hash[0] ^= 1;
+    let err = bundle.verify(&pvk, config.output_size).unwrap_err();
+    assert!(matches!(err, Error::Verification(_)));
+    assert!(err.to_string().starts_with("verification failed"));
+}
+
+#[test]
+fn decode_errors() {
+    assert!(matches!(
+        ProofBundle::read(&b"XSUMPROF"[..]),
+        Err(Error::Decode(_))
+    ));
+    assert!(matches!(
+        ProofBundle::read(&b"not a bundle"[..]),
+        Err(Error::Decode(_))
+    ));
+    assert!(matches!(
+        ProofBundle::from_json("{}"),
+        Err(Error::Decode(_))
+    ));
+}
+
+/// A header claiming a huge circuit is rejected before the circuit is
+/// synthesized to check its digest.
+#[test]
+fn oversized_headers() {
+    let header = |input_size: u32, output_size: u32| {
+        let mut header = b"XSUMVKEY".to_vec();
+        header.extend_from_slice(&params::FORMAT_VERSION.to_be_bytes());
+        header.extend_from_slice(&input_size.to_be_bytes());
+        header.extend_from_slice(&output_size.to_be_bytes());
+        header.push(0);
+        header.extend_from_slice(&[0; 32]);
+        header
+    };
+
+    assert!(matches!(
+        params::read_verifying_key(&header(u32::MAX, 1)[..]),
+        Err(Error::InvalidLength {
+            max: MAX_INPUT_SIZE,
+            ..
+        })
+    ));
+    assert!(matches!(
+        params::read_verifying_key(&header(4, 0x7fff_ffff)[..]),
+        Err(Error::InvalidLength {
+            max: MAX_OUTPUT_SIZE,
+            ..
+        })
+    ));
+
+    let mut params_header = header(u32::MAX, u32::MAX);
+    params_header[..8].copy_from_slice(b"XSUMPRMS");
+    assert!(matches!(
+        params::read_parameters(&params_header[..], false),
+        Err(Error::InvalidLength { .. })
+    ));
+}
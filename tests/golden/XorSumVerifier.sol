@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: MIT
+// Generated by bellman-test for the verifying key with fingerprint
+// 37f743df604037a1c2c8498185d886efda6aab34cb9ea7498ecf2dccaaf4fd8a.
+pragma solidity ^0.8.0;
+
+/// Verifies Groth16 proofs of knowledge of a xor-sum preimage, with the
+/// BLS12-381 precompiles of EIP-2537.
+contract XorSumVerifier {
+    /// Order of the scalar field, public inputs must be below it.
+    uint256 constant R = 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001;
+
+    address constant G1_MSM = address(0x0c);
+    address constant PAIRING_CHECK = address(0x0f);
+
+    // The verifying key, with the G2 points negated.
+    bytes constant ALPHA =
+        hex"000000000000000000000000000000000572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e"
+        hex"00000000000000000000000000000000166a9d8cabc673a322fda673779d8e3822ba3ecb8670e461f73bb9021d5fd76a4c56d9d4cd16bd1bba86881979749d28";
+    bytes constant NEG_BETA =
+        hex"00000000000000000000000000000000122915c824a0857e2ee414a3dccb23ae691ae54329781315a0c75df1c04d6d7a50a030fc866f09d516020ef82324afae"
+        hex"0000000000000000000000000000000009380275bbc8e5dcea7dc4dd7e0550ff2ac480905396eda55062650f8d251c96eb480673937cc6d9d6a44aaa56ca66dc"
+        hex"000000000000000000000000000000000edf3770e3e948394a0f2d9b87313dd62de12e66b864611834c60b67f7bb2f02d70e026a2601020d74a0bb7ec12fd219"
+        hex"00000000000000000000000000000000110ed83006e4ad324cd2d09d9fdeae7801cf6756e79350d1f5ef81ff8ff138b84f40c4a5f7de4611cfa82ac0dc5ec262";
+    bytes constant NEG_GAMMA =
+        hex"000000000000000000000000000000000411a5de6730ffece671a9f21d65028cc0f1102378de124562cb1ff49db6f004fcd14d683024b0548eff3d1468df2688"
+        hex"0000000000000000000000000000000000fb837804dba8213329db46608b6c121d973363c1234a86dd183baff112709cf97096c5e9a1a770ee9d7dc641a894d6"
+        hex"00000000000000000000000000000000004b28f464d8b76ed59a8cf5bea3b4c33303eaca2e55a8145141fe8a41c15ceb3dee3b7854913b2ebc6a818396d9ad97"
+        hex"0000000000000000000000000000000010cbaa3616f4051b64ee9613ee5ddc95762bb648f3cc59f76e0b153a93fccc987283dd4a6a5e4a217e75c1e41a556125";
+    bytes constant NEG_DELTA =
+        hex"00000000000000000000000000000000049cd1dbb2d2c3581e54c088135fef36505a6823d61b859437bfc79b617030dc8b40e32bad1fa85b9c0f368af6d38d3c"
+        hex"000000000000000000000000000000000d0273f6bf31ed37c3b8d68083ec3d8e20b5f2cc170fa24b9b5be35b34ed013f9a921f1cad1644d4bdb14674247234c8"
+        hex"000000000000000000000000000000001149639c79ffba82a4b71f73b11f186f8016a4686ab17ed0ec3d7bc6e476c6ee04c3f3c2d48b1d4ddfac073266ebddce"
+        hex"00000000000000000000000000000000141418b3e4c84511f485fcc78b80b8bc623d6f3f1282e6da09f9c1860402272ba7129c72c4fcd2174f8ac87671053a8b";
+    bytes constant IC0 =
+        hex"0000000000000000000000000000000000fd75ebcc0a21649e3177bcce15426da0e4f25d6828fbf4038d4d7ed3bd4421de3ef61d70f794687b12b2d571971a55"
+        hex"0000000000000000000000000000000004523f5a3915fc57ee889cdb057e3e76109112d125217546ccfe26810c99b130d1b27820595ad61c7527dc5bbb132a90";
+    bytes constant IC1 =
+        hex"000000000000000000000000000000000345dd80ffef0eaec8920e39ebb7f5e9ae9c1d6179e9129b705923df7830c67f3690cbc48649d4079eadf5397339580c"
+        hex"00000000000000000000000000000000083d3baf25e42f2845d8fa594dda2e0f40a4d670dda40f30da0aff0d81c87ac3d687fe84eca72f34c7c755a045668cf1";
+
+    /// Checks `proof`, the points A, B and C in the EIP-2537 encoding,
+    /// against the public inputs.
+    function verifyProof(bytes calldata proof, uint256[1] calldata input)
+        external
+        view
+        returns (bool)
+    {
+        if (proof.length != 512) {
+            return false;
+        }
+        for (uint256 i = 0; i < 1; i++) {
+            if (input[i] >= R) {
+                return false;
+            }
+        }
+
+        (bool ok, bytes memory l) = G1_MSM.staticcall(abi.encodePacked(
+            IC0, uint256(1),
+            IC1, input[0]
+        ));
+        if (!ok) {
+            return false;
+        }
+
+        bytes memory result;
+        (ok, result) = PAIRING_CHECK.staticcall(abi.encodePacked(
+            proof[0:128], proof[128:384],
+            ALPHA, NEG_BETA,
+            l, NEG_GAMMA,
+            proof[384:512], NEG_DELTA
+        ));
+        return ok && result.length == 32 && abi.decode(result, (uint256)) == 1;
+    }
+}
@@ -0,0 +1,101 @@
+//! Proving with memory-mapped parameters.
+
+use std::{fs, path::PathBuf};
+
+use bellman::groth16;
+use bellman_test::{
+    params::{self, map_parameters},
+    proof::ProofBundle,
+    CircuitConfig, Error, XorGadget,
+};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("bellman-test-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn mapped_parameters_prove_like_loaded_ones() {
+    let config = CircuitConfig {
+        input_size: 8,
+        variable_length: true,
+        output_size: 2,
+        gadget: XorGadget::Linear,
+    };
+    let params =
+        params::generate_parameters(&config, &mut ChaCha20Rng::from_seed([1; 32])).unwrap();
+    let path = temp_path("params.bin");
+    params::save_parameters(&path, &config, &params).unwrap();
+
+    for &checked in &[false, true] {
+        let (mapped_config, mapped) = map_parameters(&path, checked).unwrap();
+        assert_eq!(mapped_config, config);
+        assert!(*mapped.vk() == params.vk);
+
+        // With the same randomness, both sources give the same proof.
+        let preimage = b"mapped".to_vec();
+        let seed = [2; 32];
+        let expected = ProofBundle::create(
+            &params,
+            &config,
+            preimage.clone(),
+            &mut ChaCha20Rng::from_seed(seed),
+        )
+        .unwrap();
+        let bundle = ProofBundle::create_with_source(
+            &mapped,
+            mapped.vk(),
+            &config,
+            preimage,
+            &mut ChaCha20Rng::from_seed(seed),
+        )
+        .unwrap();
+        assert!(bundle == expected);
+        assert!(bundle
+            .verify(
+                &groth16::prepare_verifying_key(&params.vk),
+                config.output_size,
+            )
+            .is_ok());
+    }
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn malformed_mapped_parameters() {
+    let config = CircuitConfig {
+        input_size: 2,
+        ..CircuitConfig::default()
+    };
+    let params =
+        params::generate_parameters(&config, &mut ChaCha20Rng::from_seed([3; 32])).unwrap();
+    let path = temp_path("malformed.bin");
+    params::save_parameters(&path, &config, &params).unwrap();
+    let data = fs::read(&path).unwrap();
+
+    fs::write(&path, &data[..data.len() - 1]).unwrap();
+    assert!(matches!(
+        map_parameters(&path, false),
+        Err(Error::Decode(_))
+    ));
+
+    let mut trailing = data.clone();
+    trailing.push(0);
+    fs::write(&path, &trailing).unwrap();
+    assert!(matches!(
+        map_parameters(&path, false),
+        Err(Error::Decode(_))
+    ));
+
+    // A point that isn't on the curve any more is only caught up front when
+    // checked.
+    let mut corrupted = data;
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 1;
+    fs::write(&path, &corrupted).unwrap();
+    assert!(matches!(map_parameters(&path, true), Err(Error::Decode(_))));
+    assert!(map_parameters(&path, false).is_ok());
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,98 @@
+//! The multi-party ceremony for the parameters.
+
+use bellman::groth16;
+use bellman_test::{
+    mpc::{MpcParameters, PowersOfTau},
+    proof::ProofBundle,
+    CircuitConfig, Error,
+};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+fn config() -> CircuitConfig {
+    CircuitConfig {
+        input_size: 2,
+        ..CircuitConfig::default()
+    }
+}
+
+/// Both phases with two contributions each, through the file formats.
+fn ceremony(rng: &mut ChaCha20Rng) -> (PowersOfTau, MpcParameters) {
+    let mut phase1 = PowersOfTau::new(5).unwrap();
+    let transcript = vec![phase1.contribute(rng), phase1.contribute(rng)];
+    let mut encoded = vec![];
+    phase1.write(&mut encoded).unwrap();
+    let phase1 = PowersOfTau::read(&encoded[..]).unwrap();
+    assert_eq!(phase1.num_contributions(), 2);
+    assert_eq!(phase1.verify(rng).unwrap(), transcript);
+
+    let mut phase2 = MpcParameters::new(&config(), &phase1).unwrap();
+    let transcript = vec![phase2.contribute(rng), phase2.contribute(rng)];
+    let mut encoded = vec![];
+    phase2.write(&mut encoded).unwrap();
+    let phase2 = MpcParameters::read(&encoded[..]).unwrap();
+    assert_eq!(*phase2.config(), config());
+    assert_eq!(phase2.verify(&phase1, rng).unwrap(), transcript);
+
+    (phase1, phase2)
+}
+
+#[test]
+fn ceremony_parameters_prove() {
+    let mut rng = ChaCha20Rng::from_seed([5; 32]);
+    let (_, phase2) = ceremony(&mut rng);
+    let params = phase2.params();
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    let mut bundle = ProofBundle::create(params, &config(), b"ab".to_vec(), &mut rng).unwrap();
+    assert!(bundle.verify(&pvk, config().output_size).is_ok());
+    bundle.hash[0] ^= 1;
+    assert!(matches!(
+        bundle.verify(&pvk, config().output_size),
+        Err(Error::Verification(_))
+    ));
+}
+
+#[test]
+fn tampered_ceremonies_fail() {
+    let mut rng = ChaCha20Rng::from_seed([6; 32]);
+    let (phase1, phase2) = ceremony(&mut rng);
+
+    // Swapping two valid points of phase 1: tau_g1[2] and tau_g1[3], after
+    // the magic, version and power.
+    let mut encoded = vec![];
+    phase1.write(&mut encoded).unwrap();
+    let (p2, p3) = (16 + 2 * 96, 16 + 3 * 96);
+    let point = encoded[p2..p3].to_vec();
+    encoded.copy_within(p3..p3 + 96, p2);
+    encoded[p3..p3 + 96].copy_from_slice(&point);
+    let tampered = PowersOfTau::read(&encoded[..]).unwrap();
+    assert!(matches!(tampered.verify(&mut rng), Err(Error::Ceremony(_))));
+
+    // Phase 2 only verifies against the powers of tau it started from.
+    let mut other = phase1.clone();
+    other.contribute(&mut rng);
+    assert!(matches!(
+        phase2.verify(&other, &mut rng),
+        Err(Error::Ceremony(_))
+    ));
+
+    // Swapping the first two points of h, after the header, the hash of phase
+    // 1, the verifying key and the length of h.
+    let mut encoded = vec![];
+    phase2.write(&mut encoded).unwrap();
+    let h = 53 + 32 + 96 * 3 + 192 * 3 + 4 + 96 * phase2.params().vk.ic.len() + 4;
+    let point = encoded[h..h + 96].to_vec();
+    encoded.copy_within(h + 96..h + 192, h);
+    encoded[h + 96..h + 192].copy_from_slice(&point);
+    let tampered = MpcParameters::read(&encoded[..]).unwrap();
+    assert!(matches!(
+        tampered.verify(&phase1, &mut rng),
+        Err(Error::Ceremony(_))
+    ));
+
+    // Too few powers of tau for the circuit.
+    assert!(matches!(
+        MpcParameters::new(&config(), &PowersOfTau::new(1).unwrap()),
+        Err(Error::InvalidLength { .. })
+    ));
+}
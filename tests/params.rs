@@ -0,0 +1,92 @@
+//! The on-disk format of parameters and verifying keys.
+
+use std::{fs, path::PathBuf};
+
+use bellman_test::{params, CircuitConfig, Error, XorGadget};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("bellman-test-{}-{}", std::process::id(), name))
+}
+
+fn config() -> CircuitConfig {
+    CircuitConfig {
+        input_size: 4,
+        variable_length: true,
+        output_size: 2,
+        gadget: XorGadget::Linear,
+    }
+}
+
+#[test]
+fn saved_files_load() {
+    let config = config();
+    let params =
+        params::generate_parameters(&config, &mut ChaCha20Rng::from_seed([8; 32])).unwrap();
+
+    let path = temp_path("params-round-trip.bin");
+    params::save_parameters(&path, &config, &params).unwrap();
+    for &checked in &[false, true] {
+        let (loaded_config, loaded) = params::load_parameters(&path, checked).unwrap();
+        assert_eq!(loaded_config, config);
+        assert!(loaded == params);
+    }
+
+    params::save_verifying_key(&path, &config, &params.vk).unwrap();
+    let (loaded_config, vk) = params::load_verifying_key(&path).unwrap();
+    assert_eq!(loaded_config, config);
+    assert!(vk == params.vk);
+
+    fs::remove_file(&path).unwrap();
+}
+
+/// Offsets in the header: magic, version, input size, output size, flags and
+/// circuit digest.
+const VERSION: usize = 8;
+const INPUT_SIZE: usize = 12;
+const FLAGS: usize = 20;
+const DIGEST: usize = 21;
+
+#[test]
+fn bad_headers_are_rejected() {
+    let config = config();
+    let params =
+        params::generate_parameters(&config, &mut ChaCha20Rng::from_seed([9; 32])).unwrap();
+    let mut vk = vec![];
+    params::write_verifying_key(&config, &params.vk, &mut vk).unwrap();
+    let mut full = vec![];
+    params::write_parameters(&config, &params, &mut full).unwrap();
+
+    let rejected = |data: &[u8]| matches!(params::read_verifying_key(data), Err(Error::Decode(_)));
+    assert!(params::read_verifying_key(&vk[..]).is_ok());
+
+    // Parameters and verifying keys aren't mistaken for each other.
+    assert!(rejected(&full));
+    assert!(matches!(
+        params::read_parameters(&vk[..], false),
+        Err(Error::Decode(_))
+    ));
+
+    let mut bad_version = vk.clone();
+    bad_version[VERSION + 3] ^= 0x80;
+    assert!(rejected(&bad_version));
+
+    let mut bad_flags = vk.clone();
+    bad_flags[FLAGS] |= 0x80;
+    assert!(rejected(&bad_flags));
+
+    // A file generated for a different circuit: another digest, or a
+    // configuration that doesn't match the digest.
+    let mut bad_digest = vk.clone();
+    bad_digest[DIGEST] ^= 1;
+    assert!(rejected(&bad_digest));
+    let mut other_config = vk.clone();
+    other_config[INPUT_SIZE + 3] += 1;
+    assert!(rejected(&other_config));
+    let mut other_gadget = vk.clone();
+    other_gadget[FLAGS] ^= 2;
+    assert!(rejected(&other_gadget));
+
+    assert!(rejected(&vk[..DIGEST + 16]));
+    assert!(rejected(&vk[..vk.len() - 1]));
+}
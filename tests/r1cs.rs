@@ -0,0 +1,256 @@
+use bellman::{gadgets::test::TestConstraintSystem, groth16, Circuit};
+use bellman_test::{
+    cs::{record, ShapeHasher},
+    r1cs::{self, Constraint, R1cs, R1csCircuit},
+    CircuitConfig, Error, MyCircuit,
+};
+use bls12_381::{Bls12, Scalar};
+use rand::rngs::OsRng;
+
+const PREIMAGE: &[u8; 32] = b"da confusion of da highest orda!";
+const MODULUS: &str =
+    "52435875175126190479447740508185965837690552500527637822603658699938581184513";
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(buf)
+}
+
+#[test]
+fn recorded_witness_satisfies_constraints() {
+    let config = CircuitConfig::default();
+    let (r1cs, witness) =
+        record::<Scalar, _>(MyCircuit::with_preimage(config, PREIMAGE.to_vec())).unwrap();
+    let witness = witness.unwrap();
+
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    MyCircuit::with_preimage(config, PREIMAGE.to_vec())
+        .synthesize(&mut cs)
+        .unwrap();
+    assert_eq!(r1cs.constraints.len(), cs.num_constraints());
+    assert_eq!(r1cs.num_public + 1, cs.num_inputs());
+    assert!(r1cs.is_satisfied(&witness).unwrap());
+
+    // Without the witness, the constraints are the same.
+    let (without, witness) = record::<Scalar, _>(MyCircuit::new(config)).unwrap();
+    assert_eq!(without, r1cs);
+    assert_eq!(witness, None);
+}
+
+#[test]
+fn bad_witness_is_unsatisfied() {
+    let (r1cs, witness) = record::<Scalar, _>(MyCircuit::with_preimage(
+        CircuitConfig::default(),
+        PREIMAGE.to_vec(),
+    ))
+    .unwrap();
+    let mut witness = witness.unwrap();
+
+    // The public hash is wire 1, packed by the last constraint.
+    witness[1] += Scalar::one();
+    assert_eq!(
+        r1cs.which_is_unsatisfied(&witness).unwrap(),
+        Some(r1cs.constraints.len() - 1)
+    );
+}
+
+#[test]
+fn r1cs_binary_header() {
+    let (r1cs, witness) = record::<Scalar, _>(MyCircuit::with_preimage(
+        CircuitConfig::default(),
+        PREIMAGE.to_vec(),
+    ))
+    .unwrap();
+
+    let mut bytes = vec![];
+    r1cs::write_r1cs(&r1cs, &mut bytes).unwrap();
+    assert_eq!(&bytes[..4], b"r1cs");
+    assert_eq!(u32_at(&bytes, 4), 1);
+    assert_eq!(u32_at(&bytes, 8), 3);
+    // Header section: type, 64-bit size, field size, prime, wires, outputs.
+    assert_eq!(u32_at(&bytes, 12), 1);
+    assert_eq!(u32_at(&bytes, 24), 32);
+    assert_eq!(u32_at(&bytes, 60), r1cs.num_wires() as u32);
+    assert_eq!(u32_at(&bytes, 64), 1);
+
+    let mut wtns = vec![];
+    r1cs::write_wtns(&witness.unwrap(), &mut wtns).unwrap();
+    assert_eq!(&wtns[..4], b"wtns");
+    // Magic, version, sections, header section and data section headers.
+    assert_eq!(wtns.len(), 12 + 12 + 40 + 12 + 32 * r1cs.num_wires());
+}
+
+#[test]
+fn r1cs_json() {
+    let (r1cs, _) = record::<Scalar, _>(MyCircuit::new(CircuitConfig::default())).unwrap();
+    let json = r1cs::to_json(&r1cs);
+
+    assert_eq!(json["prime"], MODULUS);
+    assert_eq!(json["nVars"], r1cs.num_wires());
+    assert_eq!(json["nOutputs"], 1);
+    assert_eq!(json["nConstraints"], r1cs.constraints.len());
+    // The first constraint is the boolean constraint of the first preimage
+    // bit: (1 - bit) * bit = 0.
+    let minus_one = MODULUS.replace("513", "512");
+    assert_eq!(
+        json["constraints"][0],
+        serde_json::json!([{"0": "1", "2": minus_one}, {"2": "1"}, {}])
+    );
+}
+
+#[test]
+fn r1cs_and_wtns_round_trip() {
+    let config = CircuitConfig {
+        output_size: 2,
+        ..CircuitConfig::default()
+    };
+    let (r1cs, witness) =
+        record::<Scalar, _>(MyCircuit::with_preimage(config, PREIMAGE.to_vec())).unwrap();
+    let witness = witness.unwrap();
+
+    let mut bytes = vec![];
+    r1cs::write_r1cs(&r1cs, &mut bytes).unwrap();
+    assert_eq!(r1cs::read_r1cs::<Scalar, _>(&bytes[..]).unwrap(), r1cs);
+
+    let mut bytes = vec![];
+    r1cs::write_wtns(&witness, &mut bytes).unwrap();
+    assert_eq!(r1cs::read_wtns::<Scalar, _>(&bytes[..]).unwrap(), witness);
+}
+
+#[test]
+fn malformed_r1cs_is_rejected() {
+    let (r1cs, _) = record::<Scalar, _>(MyCircuit::new(CircuitConfig::default())).unwrap();
+    let mut bytes = vec![];
+    r1cs::write_r1cs(&r1cs, &mut bytes).unwrap();
+
+    let read = |bytes: &[u8]| r1cs::read_r1cs::<Scalar, _>(bytes).unwrap_err().to_string();
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'x';
+    assert!(read(&bad_magic).contains("bad magic"));
+
+    assert!(read(&bytes[..bytes.len() - 1]).contains("truncated"));
+
+    // The first coefficient of the first constraint, replaced by the modulus.
+    let mut non_canonical = bytes.clone();
+    let header = 12 + 12 + u32_at(&bytes, 16) as usize;
+    let coeff = header + 12 + 4 + 4;
+    non_canonical[coeff..coeff + 32].copy_from_slice(&bytes[28..60]);
+    assert!(read(&non_canonical).contains("not canonical"));
+
+    // A wire past the last one.
+    let mut out_of_range = bytes;
+    out_of_range[coeff - 4..coeff].copy_from_slice(&(r1cs.num_wires() as u32).to_le_bytes());
+    assert!(read(&out_of_range).contains("out of range"));
+}
+
+#[test]
+fn imported_circuit_has_the_same_shape() {
+    let config = CircuitConfig::default();
+    let (r1cs, _) = record::<Scalar, _>(MyCircuit::new(config)).unwrap();
+
+    let mut shape = ShapeHasher::new();
+    R1csCircuit::new(r1cs).synthesize(&mut shape).unwrap();
+    assert_eq!(shape.finish(), config.digest().unwrap());
+}
+
+#[test]
+fn recorded_terms_are_merged() {
+    let r1cs = R1cs {
+        num_public: 1,
+        num_private: 1,
+        constraints: vec![Constraint {
+            a: vec![(2, Scalar::one()), (1, Scalar::one()), (2, Scalar::one())],
+            b: vec![(0, Scalar::one())],
+            c: vec![(1, Scalar::one()), (1, -Scalar::one())],
+        }],
+    };
+    let (recorded, _) = record::<Scalar, _>(R1csCircuit::new(r1cs)).unwrap();
+    assert_eq!(
+        recorded.constraints,
+        vec![Constraint {
+            a: vec![(1, Scalar::one()), (2, Scalar::from(2))],
+            b: vec![(0, Scalar::one())],
+            c: vec![],
+        }]
+    );
+}
+
+#[test]
+fn witness_must_match_the_circuit() {
+    let (r1cs, witness) = record::<Scalar, _>(MyCircuit::with_preimage(
+        CircuitConfig::default(),
+        PREIMAGE.to_vec(),
+    ))
+    .unwrap();
+    let mut witness = witness.unwrap();
+
+    assert!(R1csCircuit::with_witness(r1cs.clone(), witness[1..].to_vec()).is_err());
+    witness[0] = Scalar::zero();
+    assert!(R1csCircuit::with_witness(r1cs, witness).is_err());
+}
+
+#[test]
+fn groth16_on_imported_circuit() {
+    // x * x = y, with y public, as circom would lay it out: "one", then the
+    // output y, then the private input x.
+    let r1cs = R1cs {
+        num_public: 1,
+        num_private: 1,
+        constraints: vec![Constraint {
+            a: vec![(2, Scalar::one())],
+            b: vec![(2, Scalar::one())],
+            c: vec![(1, Scalar::one())],
+        }],
+    };
+    let x = Scalar::from(3);
+    let witness = vec![Scalar::one(), x * x, x];
+
+    let mut bytes = vec![];
+    r1cs::write_r1cs(&r1cs, &mut bytes).unwrap();
+    let r1cs = r1cs::read_r1cs::<Scalar, _>(&bytes[..]).unwrap();
+
+    let params = groth16::generate_random_parameters::<Bls12, _, _>(
+        R1csCircuit::new(r1cs.clone()),
+        &mut OsRng,
+    )
+    .unwrap();
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    let c = R1csCircuit::with_witness(r1cs, witness).unwrap();
+    let inputs = c.public_inputs().unwrap().to_vec();
+    assert_eq!(inputs, vec![Scalar::from(9)]);
+    let proof = groth16::create_random_proof(c, &params, &mut OsRng).unwrap();
+
+    assert!(groth16::verify_proof(&pvk, &proof, &inputs).is_ok());
+    assert!(groth16::verify_proof(&pvk, &proof, &[Scalar::from(4)]).is_err());
+}
+
+#[test]
+fn out_of_range_wires_are_rejected() {
+    // y = x * x with the private x numbered past the last wire.
+    let r1cs = R1cs {
+        num_public: 1,
+        num_private: 1,
+        constraints: vec![Constraint {
+            a: vec![(3, Scalar::one())],
+            b: vec![(2, Scalar::one())],
+            c: vec![(1, Scalar::one())],
+        }],
+    };
+    let witness = vec![Scalar::one(), Scalar::from(9), Scalar::from(3)];
+
+    assert!(matches!(r1cs.check(), Err(Error::Decode(_))));
+    assert!(matches!(
+        r1cs.which_is_unsatisfied(&witness),
+        Err(Error::Decode(_))
+    ));
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    assert!(R1csCircuit::new(r1cs.clone()).synthesize(&mut cs).is_err());
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    assert!(R1csCircuit::with_witness(r1cs, witness)
+        .unwrap()
+        .synthesize(&mut cs)
+        .is_err());
+}
@@ -0,0 +1,100 @@
+//! Only built with `--features insecure-seeded-rng`.
+#![cfg(feature = "insecure-seeded-rng")]
+
+use bellman::groth16;
+use bellman_test::{batch, seeded, CircuitConfig, MyCircuit};
+use bls12_381::Bls12;
+
+const SEED: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+/// Encodings of the verifying key and of a proof for a fixed preimage.
+fn run(setup_seed: &str, proof_seed: &str) -> (Vec<u8>, Vec<u8>) {
+    let config = CircuitConfig {
+        input_size: 4,
+        ..CircuitConfig::default()
+    };
+    let mut rng = seeded::insecure_rng_from_hex(setup_seed).unwrap();
+    let params =
+        groth16::generate_random_parameters::<Bls12, _, _>(MyCircuit::new(config), &mut rng)
+            .unwrap();
+    let mut rng = seeded::insecure_rng_from_hex(proof_seed).unwrap();
+    let proof = groth16::create_random_proof(
+        MyCircuit::with_preimage(config, b"abcd".to_vec()),
+        &params,
+        &mut rng,
+    )
+    .unwrap();
+
+    let mut vk = vec![];
+    params.vk.write(&mut vk).unwrap();
+    let mut encoded = vec![];
+    proof.write(&mut encoded).unwrap();
+    (vk, encoded)
+}
+
+#[test]
+fn same_seed_same_output() {
+    let other = SEED.replace("00", "ff");
+    let (vk, proof) = run(SEED, SEED);
+
+    assert_eq!(run(SEED, SEED), (vk.clone(), proof.clone()));
+    let (other_vk, other_proof) = run(&other, SEED);
+    assert_ne!(other_vk, vk);
+    assert_ne!(other_proof, proof);
+    let (same_vk, other_proof) = run(SEED, &other);
+    assert_eq!(same_vk, vk);
+    assert_ne!(other_proof, proof);
+}
+
+#[test]
+fn bad_seeds_are_rejected() {
+    assert!(seeded::insecure_rng_from_hex("00").is_err());
+    assert!(seeded::insecure_rng_from_hex(&SEED.replace("00", "zz")).is_err());
+}
+
+#[test]
+fn batches_are_reproducible() {
+    let config = CircuitConfig {
+        input_size: 4,
+        ..CircuitConfig::default()
+    };
+    let seed = seeded::insecure_seed_from_hex(SEED).unwrap();
+    let params = groth16::generate_random_parameters::<Bls12, _, _>(
+        MyCircuit::new(config),
+        &mut seeded::insecure_rng(seed),
+    )
+    .unwrap();
+    let preimages = vec![b"abcd".to_vec(), b"efgh".to_vec(), b"abcd".to_vec()];
+    let prove = |threads| -> Vec<Vec<u8>> {
+        batch::prove_batch(
+            &params,
+            config,
+            &preimages,
+            threads,
+            |i| seeded::insecure_batch_rng(seed, i),
+            |_, _| {},
+        )
+        .into_iter()
+        .map(|proof| {
+            let mut encoded = vec![];
+            proof.unwrap().write(&mut encoded).unwrap();
+            encoded
+        })
+        .collect()
+    };
+
+    let proofs = prove(1);
+    assert_eq!(prove(3), proofs);
+    // Each proof gets its own stream, even for the same preimage.
+    assert_ne!(proofs[0], proofs[2]);
+
+    let single = groth16::create_random_proof(
+        MyCircuit::with_preimage(config, b"abcd".to_vec()),
+        &params,
+        &mut seeded::insecure_rng(seed),
+    )
+    .unwrap();
+    let mut encoded = vec![];
+    single.write(&mut encoded).unwrap();
+    assert_eq!(proofs[0], encoded);
+}
@@ -0,0 +1,133 @@
+use bellman::groth16::{self, Parameters, Proof};
+use bellman_test::{
+    native_xor_fold, params, proof::ProofBundle, snarkjs, CircuitConfig, Error, MyCircuit,
+};
+use bls12_381::{Bls12, G1Affine, Scalar};
+use rand::rngs::OsRng;
+
+const PREIMAGE: &[u8; 32] = b"da confusion of da highest orda!";
+
+// Coordinates of the generator of G1.
+const G1_X: &str = "3685416753713387016781088315183077757961620795782546409894578378688607592378376318836054947676345821548104185464507";
+const G1_Y: &str = "1339506544944476473020471379941921221584933875938349620426543736416511423956333506472724655353366534992391756441569";
+
+fn prove(config: CircuitConfig) -> (Parameters<Bls12>, ProofBundle) {
+    let params =
+        groth16::generate_random_parameters::<Bls12, _, _>(MyCircuit::new(config), &mut OsRng)
+            .unwrap();
+    let proof = groth16::create_random_proof(
+        MyCircuit::with_preimage(config, PREIMAGE.to_vec()),
+        &params,
+        &mut OsRng,
+    )
+    .unwrap();
+    let hash = native_xor_fold(PREIMAGE, config.output_size).unwrap();
+    let bundle = ProofBundle::new(proof, hash, &params.vk);
+    (params, bundle)
+}
+
+#[test]
+fn points_are_decimal_coordinates() {
+    let proof = Proof::<Bls12> {
+        a: G1Affine::generator(),
+        b: bls12_381::G2Affine::identity(),
+        c: G1Affine::identity(),
+    };
+    let json: serde_json::Value = serde_json::from_str(&snarkjs::proof_to_json(&proof)).unwrap();
+    assert_eq!(json["pi_a"], serde_json::json!([G1_X, G1_Y, "1"]));
+    assert_eq!(
+        json["pi_b"],
+        serde_json::json!([["0", "0"], ["1", "0"], ["0", "0"]])
+    );
+    assert_eq!(json["pi_c"], serde_json::json!(["0", "1", "0"]));
+    assert_eq!(json["protocol"], "groth16");
+    assert_eq!(json["curve"], "bls12381");
+
+    assert!(snarkjs::proof_from_json(&snarkjs::proof_to_json(&proof)).unwrap() == proof);
+}
+
+#[test]
+fn round_trip_and_verify() {
+    let config = CircuitConfig {
+        output_size: 40,
+        ..CircuitConfig::default()
+    };
+    let (params, bundle) = prove(config);
+
+    let proof = snarkjs::proof_from_json(&snarkjs::proof_to_json(&bundle.proof)).unwrap();
+    assert!(proof == bundle.proof);
+
+    let expected = bundle.public_inputs(config.output_size).unwrap();
+    let inputs =
+        snarkjs::public_inputs_from_json(&snarkjs::public_inputs_to_json(&expected)).unwrap();
+    assert_eq!(inputs, expected);
+    // 320 hash bits don't fit into a single input.
+    assert_eq!(inputs.len(), 2);
+
+    let json = snarkjs::verifying_key_to_json(&params.vk);
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["nPublic"], inputs.len());
+    let vk = snarkjs::verifying_key_from_json(&json).unwrap();
+    assert_eq!(vk.alpha_g1, params.vk.alpha_g1);
+    assert_eq!(vk.beta_g2, params.vk.beta_g2);
+    assert_eq!(vk.gamma_g2, params.vk.gamma_g2);
+    assert_eq!(vk.delta_g2, params.vk.delta_g2);
+    assert_eq!(vk.ic, params.vk.ic);
+
+    // The decoded key verifies proofs despite the missing G1 points.
+    let pvk = groth16::prepare_verifying_key(&vk);
+    assert!(groth16::verify_proof(&pvk, &proof, &inputs).is_ok());
+    let mut wrong = inputs;
+    wrong[0] += Scalar::one();
+    assert!(groth16::verify_proof(&pvk, &proof, &wrong).is_err());
+}
+
+#[test]
+fn imported_keys_are_not_persisted() {
+    let config = CircuitConfig::default();
+    let (params, _) = prove(config);
+    let vk = snarkjs::verifying_key_from_json(&snarkjs::verifying_key_to_json(&params.vk)).unwrap();
+
+    let mut encoded = vec![];
+    assert!(matches!(
+        params::write_verifying_key(&config, &vk, &mut encoded),
+        Err(Error::Decode(_))
+    ));
+    let path = std::env::temp_dir().join(format!(
+        "bellman-test-{}-snarkjs-vk.bin",
+        std::process::id()
+    ));
+    assert!(matches!(
+        params::save_verifying_key(&path, &config, &vk),
+        Err(Error::Decode(_))
+    ));
+    assert!(!path.exists());
+}
+
+#[test]
+fn malformed_json_is_rejected() {
+    let (params, bundle) = prove(CircuitConfig::default());
+    let proof = snarkjs::proof_to_json(&bundle.proof);
+    let vk = snarkjs::verifying_key_to_json(&params.vk);
+
+    let wrong_curve = proof.replace("bls12381", "bn128");
+    assert!(snarkjs::proof_from_json(&wrong_curve).is_err());
+
+    // A coordinate moved off the curve.
+    let mut value: serde_json::Value = serde_json::from_str(&proof).unwrap();
+    value["pi_a"][0] = "1".into();
+    assert!(snarkjs::proof_from_json(&value.to_string()).is_err());
+
+    let mut value: serde_json::Value = serde_json::from_str(&vk).unwrap();
+    value["nPublic"] = 2.into();
+    assert!(snarkjs::verifying_key_from_json(&value.to_string()).is_err());
+    // Doesn't overflow counting the IC points.
+    value["nPublic"] = usize::MAX.into();
+    assert!(snarkjs::verifying_key_from_json(&value.to_string()).is_err());
+
+    // The scalar field modulus is not reduced.
+    let modulus =
+        r#"["52435875175126190479447740508185965837690552500527637822603658699938581184513"]"#;
+    assert!(snarkjs::public_inputs_from_json(modulus).is_err());
+    assert!(snarkjs::public_inputs_from_json(r#"["0x16"]"#).is_err());
+}
@@ -0,0 +1,211 @@
+//! The generated Solidity source and the encoding of its calldata. The
+//! contract itself is neither compiled nor run: the source is compared with
+//! golden files, and the calldata is checked against a Rust model of what the
+//! contract does with it.
+
+use std::{convert::TryInto, fs, path::Path};
+
+use bellman::groth16::{self, Proof, VerifyingKey};
+use bellman_test::{native_xor_fold, proof::ProofBundle, solidity, CircuitConfig, MyCircuit};
+use bls12_381::{
+    multi_miller_loop, Bls12, G1Affine, G1Projective, G2Affine, G2Prepared, Gt, Scalar,
+};
+use ff::PrimeField;
+use rand::rngs::OsRng;
+
+const PREIMAGE: &[u8; 32] = b"da confusion of da highest orda!";
+
+/// Compares `actual` with a file in `tests/golden`, or overwrites the file if
+/// `UPDATE_GOLDEN` is set.
+fn check_golden(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).unwrap();
+    }
+    let expected = fs::read_to_string(&path).unwrap();
+    assert!(
+        actual == expected,
+        "{} is out of date, rerun with UPDATE_GOLDEN=1",
+        path.display()
+    );
+}
+
+/// A verifying key made of small multiples of the generators.
+fn fixed_vk(num_inputs: u64) -> VerifyingKey<Bls12> {
+    let g1 = |k: u64| G1Affine::from(G1Affine::generator() * Scalar::from(k));
+    let g2 = |k: u64| G2Affine::from(G2Affine::generator() * Scalar::from(k));
+    VerifyingKey {
+        alpha_g1: g1(2),
+        beta_g1: g1(3),
+        beta_g2: g2(3),
+        gamma_g2: g2(5),
+        delta_g1: g1(7),
+        delta_g2: g2(7),
+        ic: (0..=num_inputs).map(|i| g1(11 + i)).collect(),
+    }
+}
+
+fn decode_fp(bytes: &[u8]) -> Option<&[u8]> {
+    if bytes[..16].iter().any(|&b| b != 0) {
+        return None;
+    }
+    Some(&bytes[16..])
+}
+
+fn decode_g1(bytes: &[u8]) -> Option<G1Affine> {
+    if bytes.iter().all(|&b| b == 0) {
+        return Some(G1Affine::identity());
+    }
+    let mut uncompressed = [0; 96];
+    uncompressed[..48].copy_from_slice(decode_fp(&bytes[..64])?);
+    uncompressed[48..].copy_from_slice(decode_fp(&bytes[64..])?);
+    G1Affine::from_uncompressed(&uncompressed).into()
+}
+
+fn decode_g2(bytes: &[u8]) -> Option<G2Affine> {
+    if bytes.iter().all(|&b| b == 0) {
+        return Some(G2Affine::identity());
+    }
+    let mut uncompressed = [0; 192];
+    for (i, &j) in [1, 0, 3, 2].iter().enumerate() {
+        uncompressed[48 * i..48 * (i + 1)]
+            .copy_from_slice(decode_fp(&bytes[64 * j..64 * (j + 1)])?);
+    }
+    G2Affine::from_uncompressed(&uncompressed).into()
+}
+
+/// Decodes `calldata` with the encodings the contract is written for, and
+/// runs the same pairing check in Rust.
+fn verify_calldata(vk: &VerifyingKey<Bls12>, calldata: &[u8]) -> bool {
+    let num_inputs = vk.ic.len() - 1;
+    if calldata[..4] != solidity::selector(num_inputs) {
+        return false;
+    }
+    let words = &calldata[4..];
+    let word = |i: usize| &words[32 * i..32 * (i + 1)];
+
+    let offset = u64::from_be_bytes(word(0)[24..].try_into().unwrap()) as usize;
+    let len = u64::from_be_bytes(words[offset + 24..offset + 32].try_into().unwrap()) as usize;
+    let proof = &words[offset + 32..offset + 32 + len];
+    if len != solidity::PROOF_SIZE {
+        return false;
+    }
+
+    let mut l = G1Projective::from(vk.ic[0]);
+    for i in 0..num_inputs {
+        let mut repr = [0; 32];
+        repr.copy_from_slice(word(1 + i));
+        repr.reverse();
+        // Inputs at or above the modulus don't decode.
+        let input = match Scalar::from_repr(repr) {
+            Some(input) => input,
+            None => return false,
+        };
+        l += vk.ic[i + 1] * input;
+    }
+
+    let points = (
+        decode_g1(&proof[..128]),
+        decode_g2(&proof[128..384]),
+        decode_g1(&proof[384..]),
+    );
+    let (a, b, c) = match points {
+        (Some(a), Some(b), Some(c)) => (a, b, c),
+        _ => return false,
+    };
+    let terms = [
+        (&a, &G2Prepared::from(b)),
+        (&vk.alpha_g1, &G2Prepared::from(-vk.beta_g2)),
+        (&G1Affine::from(l), &G2Prepared::from(-vk.gamma_g2)),
+        (&c, &G2Prepared::from(-vk.delta_g2)),
+    ];
+    multi_miller_loop(&terms).final_exponentiation() == Gt::identity()
+}
+
+#[test]
+fn contract_source_matches_golden_file() {
+    check_golden(
+        "XorSumVerifier.sol",
+        &solidity::verifier_contract(&fixed_vk(1)).unwrap(),
+    );
+    check_golden(
+        "XorSumVerifier3.sol",
+        &solidity::verifier_contract(&fixed_vk(3)).unwrap(),
+    );
+}
+
+#[test]
+fn calldata_layout() {
+    let proof = Proof::<Bls12> {
+        a: G1Affine::generator(),
+        b: G2Affine::generator(),
+        c: G1Affine::identity(),
+    };
+    let calldata = solidity::calldata(&proof, &[Scalar::from(22), Scalar::from(7)]);
+
+    assert_eq!(solidity::signature(2), "verifyProof(bytes,uint256[2])");
+    assert_eq!(&calldata[..4], &solidity::selector(2));
+    assert_eq!(calldata.len(), 4 + 32 * 4 + solidity::PROOF_SIZE);
+    // Offset of the proof, the inputs, then the length of the proof.
+    assert_eq!(calldata[4 + 31], 96);
+    assert_eq!(calldata[4 + 32 + 31], 22);
+    assert_eq!(calldata[4 + 64 + 31], 7);
+    assert_eq!(&calldata[4 + 96 + 30..4 + 128], &[2, 0]);
+    assert_eq!(&calldata[4 + 128..], &solidity::encode_proof(&proof)[..]);
+    assert_eq!(decode_g1(&calldata[4 + 128..4 + 256]), Some(proof.a));
+    assert!(calldata[4 + 128 + 384..].iter().all(|&b| b == 0));
+}
+
+#[test]
+fn calldata_agrees_with_verify_proof() {
+    let config = CircuitConfig::default();
+    let params =
+        groth16::generate_random_parameters::<Bls12, _, _>(MyCircuit::new(config), &mut OsRng)
+            .unwrap();
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+    let proof = groth16::create_random_proof(
+        MyCircuit::with_preimage(config, PREIMAGE.to_vec()),
+        &params,
+        &mut OsRng,
+    )
+    .unwrap();
+    let bundle = ProofBundle::new(proof, native_xor_fold(PREIMAGE, 1).unwrap(), &params.vk);
+    let inputs = bundle.public_inputs(1).unwrap();
+
+    let wrong_inputs = vec![inputs[0] + Scalar::one()];
+    let mut swapped = bundle.proof.clone();
+    swapped.a = bundle.proof.c;
+    swapped.c = bundle.proof.a;
+
+    for (proof, inputs) in &[
+        (&bundle.proof, &inputs),
+        (&bundle.proof, &wrong_inputs),
+        (&swapped, &inputs),
+    ] {
+        let expected = groth16::verify_proof(&pvk, proof, inputs).is_ok();
+        let calldata = solidity::calldata(proof, inputs);
+        assert_eq!(verify_calldata(&params.vk, &calldata), expected);
+    }
+    assert!(verify_calldata(
+        &params.vk,
+        &solidity::calldata(&bundle.proof, &inputs)
+    ));
+
+    // The same input plus the modulus must not be accepted.
+    let mut calldata = solidity::calldata(&bundle.proof, &inputs);
+    let mut carry = 0u16;
+    let mut modulus = (-Scalar::one()).to_repr();
+    modulus[0] += 1;
+    for (byte, m) in calldata[4 + 32..4 + 64]
+        .iter_mut()
+        .rev()
+        .zip(modulus.iter())
+    {
+        let sum = u16::from(*byte) + u16::from(*m) + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+    assert!(!verify_calldata(&params.vk, &calldata));
+}
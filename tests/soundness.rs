@@ -0,0 +1,351 @@
+//! Soundness tests: take a valid witness of `MyCircuit`, change it in every
+//! way we can think of and check that the change is caught, either by
+//! `TestConstraintSystem` (reporting the first unsatisfied constraint) or by
+//! `groth16::verify_proof`.
+
+use std::collections::HashMap;
+
+use bellman::{
+    gadgets::{multipack, test::TestConstraintSystem},
+    groth16, Circuit, ConstraintSystem, LinearCombination, SynthesisError, Variable,
+};
+use bellman_test::{native_xor_fold, CircuitConfig, MyCircuit, XorGadget};
+use bls12_381::{Bls12, Scalar};
+use rand::rngs::OsRng;
+
+const PREIMAGE: &[u8; 32] = b"da confusion of da highest orda!";
+
+/// A circuit whose assignments at the given paths are replaced with other
+/// values, while the constraints stay the same.
+struct Mutated<C> {
+    circuit: C,
+    overrides: HashMap<String, Scalar>,
+}
+
+/// Wraps a constraint system, tracking namespaces to find the paths of
+/// allocated variables.
+struct Mutating<'a, CS> {
+    inner: &'a mut CS,
+    namespace: Vec<String>,
+    overrides: &'a mut HashMap<String, Scalar>,
+}
+
+impl<'a, CS: ConstraintSystem<Scalar>> Mutating<'a, CS> {
+    fn value<F>(&mut self, name: &str, f: F) -> Result<Scalar, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+    {
+        let mut path = self.namespace.clone();
+        path.push(name.to_string());
+
+        // Gadgets track values of their outputs from inside `f`, so it has to
+        // run even if its result is thrown away.
+        let value = f()?;
+        Ok(self.overrides.remove(&path.join("/")).unwrap_or(value))
+    }
+}
+
+impl<'a, CS: ConstraintSystem<Scalar>> ConstraintSystem<Scalar> for Mutating<'a, CS> {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let name = annotation().into();
+        let value = self.value(&name, f);
+        self.inner.alloc(|| name, || value)
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let name = annotation().into();
+        let value = self.value(&name, f);
+        self.inner.alloc_input(|| name, || value)
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+        self.inner.enforce(annotation, a, b, c)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name = name_fn().into();
+        self.namespace.push(name.clone());
+        self.inner.get_root().push_namespace(|| name);
+    }
+
+    fn pop_namespace(&mut self) {
+        self.namespace.pop();
+        self.inner.get_root().pop_namespace();
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+impl<C: Circuit<Scalar>> Circuit<Scalar> for Mutated<C> {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        mut self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let mut cs = Mutating {
+            inner: cs,
+            namespace: vec![],
+            overrides: &mut self.overrides,
+        };
+        self.circuit.synthesize(&mut cs)?;
+
+        let missed = self.overrides.keys().collect::<Vec<_>>();
+        assert!(missed.is_empty(), "no variables at {:?}", missed);
+        Ok(())
+    }
+}
+
+fn honest(config: CircuitConfig, preimage: &[u8]) -> TestConstraintSystem<Scalar> {
+    let mut cs = TestConstraintSystem::new();
+    MyCircuit::with_preimage(config, preimage.to_vec())
+        .synthesize(&mut cs)
+        .unwrap();
+    assert!(cs.is_satisfied());
+    cs
+}
+
+/// Synthesizes the circuit with `overrides` and returns the path of the first
+/// unsatisfied constraint, if any.
+fn first_unsatisfied(
+    config: CircuitConfig,
+    preimage: &[u8],
+    overrides: &[(String, Scalar)],
+) -> Option<String> {
+    let mut cs = TestConstraintSystem::new();
+    Mutated {
+        circuit: MyCircuit::with_preimage(config, preimage.to_vec()),
+        overrides: overrides.iter().cloned().collect(),
+    }
+    .synthesize(&mut cs)
+    .unwrap();
+    cs.which_is_unsatisfied().map(String::from)
+}
+
+fn flip(value: Scalar) -> Scalar {
+    Scalar::one() - value
+}
+
+fn bit_path(i: usize) -> String {
+    format!("preimage bit {}/boolean", i)
+}
+
+fn configs() -> Vec<CircuitConfig> {
+    let mut configs = vec![];
+    for &variable_length in &[false, true] {
+        for &output_size in &[1, 4] {
+            for gadget in XorGadget::ALL.iter().copied() {
+                configs.push(CircuitConfig {
+                    variable_length,
+                    output_size,
+                    gadget,
+                    ..CircuitConfig::default()
+                });
+            }
+        }
+    }
+    configs
+}
+
+#[test]
+fn flipped_preimage_bits_are_caught() {
+    for config in configs() {
+        let preimage = if config.variable_length {
+            &PREIMAGE[..20]
+        } else {
+            &PREIMAGE[..]
+        };
+        let mut cs = honest(config, preimage);
+
+        for i in 0..config.input_size * 8 {
+            let path = bit_path(i);
+            let unsatisfied =
+                first_unsatisfied(config, preimage, &[(path.clone(), flip(cs.get(&path)))]);
+
+            if i / 8 >= preimage.len() {
+                // Padding is masked out, it can be anything.
+                assert_eq!(unsatisfied, None, "{:?}: {}", config, path);
+            } else {
+                // The bit is still boolean, so it's the gadget using it that
+                // breaks.
+                let unsatisfied = unsatisfied.unwrap_or_else(|| panic!("{:?}: {}", config, path));
+                let gadget = if config.variable_length {
+                    "mask(preimage)/"
+                } else {
+                    "xor_sum(preimage)/"
+                };
+                assert!(
+                    unsatisfied.starts_with(gadget),
+                    "{:?}: {} broke {}",
+                    config,
+                    path,
+                    unsatisfied
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn non_boolean_preimage_bits_are_caught() {
+    for config in configs() {
+        for &i in &[0, 7, 100, 255] {
+            let two = Scalar::one().double();
+            let unsatisfied = first_unsatisfied(config, &PREIMAGE[..], &[(bit_path(i), two)]);
+
+            let expected = format!("preimage bit {}/boolean constraint", i);
+            assert_eq!(
+                unsatisfied.as_deref(),
+                Some(expected.as_str()),
+                "{:?}",
+                config
+            );
+        }
+    }
+}
+
+#[test]
+fn corrupted_public_inputs_are_caught() {
+    for config in configs() {
+        let mut cs = honest(config, &PREIMAGE[..]);
+        let value = cs.get_input(1, "pack hash/input 0");
+
+        for corrupted in [
+            value + Scalar::one(),
+            value.double(),
+            value.neg() - Scalar::one(),
+        ]
+        .iter()
+        {
+            let overrides = [("pack hash/input 0".to_string(), *corrupted)];
+            let unsatisfied = first_unsatisfied(config, &PREIMAGE[..], &overrides);
+
+            assert_eq!(
+                unsatisfied.as_deref(),
+                Some("pack hash/packing constraint 0"),
+                "{:?}",
+                config
+            );
+        }
+    }
+}
+
+#[test]
+fn swapped_xor_results_are_caught() {
+    let config = CircuitConfig::default();
+    let mut cs = honest(config, &PREIMAGE[..]);
+
+    let mut caught = 0;
+    for i in 1..32 {
+        for j in 0..8 {
+            let a = format!("xor_sum(preimage)/xor [{}][{}]/xor result", i, j);
+            let b = format!("xor_sum(preimage)/xor [{}][{}]/xor result", i, (j + 1) % 8);
+            let (value_a, value_b) = (cs.get(&a), cs.get(&b));
+            if value_a == value_b {
+                // Swapping equal values changes nothing.
+                continue;
+            }
+
+            let unsatisfied =
+                first_unsatisfied(config, &PREIMAGE[..], &[(a.clone(), value_b), (b, value_a)]);
+            let expected = format!(
+                "xor_sum(preimage)/xor [{}][{}]/xor constraint",
+                i,
+                j.min((j + 1) % 8)
+            );
+            assert_eq!(unsatisfied, Some(expected));
+            caught += 1;
+        }
+    }
+    assert!(caught > 0);
+}
+
+#[test]
+fn swapped_parity_bits_are_caught() {
+    let config = CircuitConfig {
+        gadget: XorGadget::Linear,
+        ..CircuitConfig::default()
+    };
+    let mut cs = honest(config, &PREIMAGE[..]);
+
+    for p in 0..8 {
+        let parity = format!("xor_sum(preimage)/parity [{}]/parity/boolean", p);
+        let quotient = format!("xor_sum(preimage)/parity [{}]/quotient bit 0/boolean", p);
+        let (value_p, value_q) = (cs.get(&parity), cs.get(&quotient));
+        if value_p == value_q {
+            continue;
+        }
+
+        let unsatisfied = first_unsatisfied(
+            config,
+            &PREIMAGE[..],
+            &[(parity, value_q), (quotient, value_p)],
+        );
+        let expected = format!("xor_sum(preimage)/parity [{}]/sum decomposition", p);
+        assert_eq!(unsatisfied, Some(expected));
+    }
+}
+
+#[test]
+fn groth16_rejects_mutations() {
+    let config = CircuitConfig::default();
+    let params =
+        groth16::generate_random_parameters::<Bls12, _, _>(MyCircuit::new(config), &mut OsRng)
+            .unwrap();
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    let hash = native_xor_fold(PREIMAGE, config.output_size).unwrap();
+    let inputs = multipack::compute_multipacking(&multipack::bytes_to_bits_le(&hash));
+
+    let prove = |overrides: &[(String, Scalar)]| {
+        let circuit = Mutated {
+            circuit: MyCircuit::with_preimage(config, PREIMAGE.to_vec()),
+            overrides: overrides.iter().cloned().collect(),
+        };
+        groth16::create_random_proof(circuit, &params, &mut OsRng).unwrap()
+    };
+
+    // The honest proof verifies, but not for any other public input.
+    let proof = prove(&[]);
+    assert!(groth16::verify_proof(&pvk, &proof, &inputs).is_ok());
+    let other = [inputs[0] + Scalar::one()];
+    assert!(groth16::verify_proof(&pvk, &proof, &other).is_err());
+
+    let mut cs = honest(config, &PREIMAGE[..]);
+    let mutations = vec![
+        vec![(bit_path(42), flip(cs.get(&bit_path(42))))],
+        vec![(bit_path(42), Scalar::one().double())],
+        vec![("pack hash/input 0".to_string(), inputs[0] + Scalar::one())],
+    ];
+    for overrides in mutations {
+        let proof = prove(&overrides);
+        assert!(
+            groth16::verify_proof(&pvk, &proof, &inputs).is_err(),
+            "{:?}",
+            overrides
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use bellman_test::{
+    cs::{stats, NamespaceStats},
+    CircuitConfig, MyCircuit, XorGadget,
+};
+use bls12_381::Scalar;
+
+fn group(name: &str, constraints: usize, aux: usize, inputs: usize) -> NamespaceStats {
+    NamespaceStats {
+        name: name.to_string(),
+        constraints,
+        aux,
+        inputs,
+    }
+}
+
+#[test]
+fn default_circuit_breakdown() {
+    let stats = stats::<Scalar, _>(MyCircuit::new(CircuitConfig::default()), 1).unwrap();
+
+    assert_eq!(stats.constraints, 505);
+    assert_eq!(stats.aux, 504);
+    assert_eq!(stats.inputs, 2);
+    assert_eq!(
+        stats.namespaces,
+        [
+            group("pack hash", 1, 0, 1),
+            group("preimage bit i", 256, 256, 0),
+            group("xor_sum(preimage)", 248, 248, 0),
+        ]
+    );
+}
+
+#[test]
+fn totals_match_shape() {
+    for gadget in XorGadget::ALL.iter().copied() {
+        let config = CircuitConfig {
+            variable_length: true,
+            output_size: 3,
+            gadget,
+            ..CircuitConfig::default()
+        };
+        let shape = config.shape().unwrap();
+
+        for depth in 0..4 {
+            let stats = stats::<Scalar, _>(MyCircuit::new(config), depth).unwrap();
+            assert_eq!(stats.constraints, shape.num_constraints());
+            assert_eq!(stats.aux, shape.num_aux());
+            assert_eq!(stats.inputs, shape.num_inputs());
+
+            let sum =
+                |f: fn(&NamespaceStats) -> usize| stats.namespaces.iter().map(f).sum::<usize>();
+            assert_eq!(sum(|ns| ns.constraints), stats.constraints);
+            assert_eq!(sum(|ns| ns.aux), stats.aux);
+            // The "one" input isn't allocated in any namespace.
+            assert_eq!(sum(|ns| ns.inputs), stats.inputs - 1);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Known-answer tests: fixed seeds must keep producing byte-identical
+//! verifying keys and proofs, so that any change to the constraints of the
+//! circuit, their order or their namespaces fails here instead of silently
+//! invalidating deployed keys. Keys and proofs never see namespaces, so the
+//! vectors also hold a digest of the names of every constraint and variable.
+//!
+//! The vectors live in `tests/golden/vectors.json`. They use the RNG of the
+//! `insecure-seeded-rng` feature, so `setup --insecure-seed` and
+//! `prove --insecure-seed` reproduce them; without the feature, the same
+//! ChaCha20 RNG is seeded here. Rerun with `UPDATE_GOLDEN=1` to regenerate
+//! them after an intended change to the circuit.
+
+use std::{fs, path::Path};
+
+use bellman::{
+    gadgets::test::TestConstraintSystem,
+    groth16::{self, Proof, VerifyingKey},
+    Circuit,
+};
+use bellman_test::{native_xor_fold, proof::ProofBundle, CircuitConfig, MyCircuit, XorGadget};
+use blake2s_simd::Params as Blake2sParams;
+use bls12_381::{Bls12, Scalar};
+use ff::PrimeField;
+#[cfg(not(feature = "insecure-seeded-rng"))]
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Vector {
+    input_size: usize,
+    variable_length: bool,
+    output_size: usize,
+    gadget: String,
+    setup_seed: String,
+    proof_seed: String,
+    preimage: String,
+    /// See `CircuitConfig::digest`.
+    circuit_digest: String,
+    /// BLAKE2s digest of `TestConstraintSystem::pretty_print`, which lists
+    /// the constraints with the full paths of their namespaces.
+    names_digest: String,
+    /// bellman's encoding of the verifying key.
+    vk: String,
+    /// bellman's encoding of the proof.
+    proof: String,
+    /// Little-endian encoding of each public input.
+    public_inputs: Vec<String>,
+}
+
+#[cfg(feature = "insecure-seeded-rng")]
+fn rng(seed: &str) -> bellman_test::seeded::InsecureRng {
+    bellman_test::seeded::insecure_rng_from_hex(seed).unwrap()
+}
+
+#[cfg(not(feature = "insecure-seeded-rng"))]
+fn rng(seed: &str) -> ChaCha20Rng {
+    let mut bytes = [0; 32];
+    bytes.copy_from_slice(&hex::decode(seed).unwrap());
+    ChaCha20Rng::from_seed(bytes)
+}
+
+fn names_digest(config: CircuitConfig, preimage: Vec<u8>) -> String {
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    MyCircuit::with_preimage(config, preimage)
+        .synthesize(&mut cs)
+        .unwrap();
+    hex::encode(
+        Blake2sParams::new()
+            .hash_length(32)
+            .hash(cs.pretty_print().as_bytes())
+            .as_bytes(),
+    )
+}
+
+/// Recomputes the outputs of `vector` from its inputs.
+fn compute(vector: &Vector) -> Vector {
+    let config = CircuitConfig {
+        input_size: vector.input_size,
+        variable_length: vector.variable_length,
+        output_size: vector.output_size,
+        gadget: vector.gadget.parse::<XorGadget>().unwrap(),
+    };
+    let preimage = hex::decode(&vector.preimage).unwrap();
+
+    let params = groth16::generate_random_parameters::<Bls12, _, _>(
+        MyCircuit::new(config),
+        &mut rng(&vector.setup_seed),
+    )
+    .unwrap();
+    let proof = groth16::create_random_proof(
+        MyCircuit::with_preimage(config, preimage.clone()),
+        &params,
+        &mut rng(&vector.proof_seed),
+    )
+    .unwrap();
+    let hash = native_xor_fold(&preimage, config.output_size).unwrap();
+    let bundle = ProofBundle::new(proof, hash, &params.vk);
+
+    let mut vk = vec![];
+    params.vk.write(&mut vk).unwrap();
+    let mut proof = vec![];
+    bundle.proof.write(&mut proof).unwrap();
+    Vector {
+        input_size: vector.input_size,
+        variable_length: vector.variable_length,
+        output_size: vector.output_size,
+        gadget: vector.gadget.clone(),
+        setup_seed: vector.setup_seed.clone(),
+        proof_seed: vector.proof_seed.clone(),
+        preimage: vector.preimage.clone(),
+        circuit_digest: hex::encode(config.digest().unwrap()),
+        names_digest: names_digest(config, preimage),
+        vk: hex::encode(vk),
+        proof: hex::encode(proof),
+        public_inputs: bundle
+            .public_inputs(config.output_size)
+            .unwrap()
+            .iter()
+            .map(|input| hex::encode(input.to_repr()))
+            .collect(),
+    }
+}
+
+#[test]
+fn known_answers() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/vectors.json");
+    let vectors: Vec<Vector> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert!(!vectors.is_empty());
+
+    let computed = vectors.iter().map(compute).collect::<Vec<_>>();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(
+            &path,
+            serde_json::to_string_pretty(&computed).unwrap() + "\n",
+        )
+        .unwrap();
+        return;
+    }
+
+    for (vector, computed) in vectors.iter().zip(&computed) {
+        // The vectors must also be consistent on their own.
+        let vk = VerifyingKey::<Bls12>::read(&hex::decode(&vector.vk).unwrap()[..]).unwrap();
+        let proof = Proof::<Bls12>::read(&hex::decode(&vector.proof).unwrap()[..]).unwrap();
+        let inputs = vector
+            .public_inputs
+            .iter()
+            .map(|input| {
+                let mut repr = [0; 32];
+                repr.copy_from_slice(&hex::decode(input).unwrap());
+                bls12_381::Scalar::from_repr(repr).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let pvk = groth16::prepare_verifying_key(&vk);
+        assert!(groth16::verify_proof(&pvk, &proof, &inputs).is_ok());
+
+        assert_eq!(
+            computed, vector,
+            "outputs changed, rerun with UPDATE_GOLDEN=1 if that is intended"
+        );
+    }
+}
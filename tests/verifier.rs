@@ -0,0 +1,163 @@
+//! The verifier-only build: `--no-default-features --features verifier`.
+//!
+//! The verifying key and bundles in `tests/golden` are made by the prover with
+//! a fixed seed; rerun with `UPDATE_GOLDEN=1` in a build with the `prover`
+//! feature to regenerate them. A regular build also checks the dependencies of
+//! the verifier-only build and runs this file in it.
+#![cfg(feature = "verifier")]
+
+use std::{fs, path::PathBuf};
+
+use bellman::groth16;
+use bellman_test::{batch::BatchVerifier, params, proof::ProofBundle, Error};
+use rand::rngs::OsRng;
+
+fn golden(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name)
+}
+
+#[cfg(feature = "prover")]
+fn update_golden() {
+    use bellman_test::{params::generate_parameters, CircuitConfig};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+    let config = CircuitConfig {
+        input_size: 8,
+        output_size: 2,
+        ..CircuitConfig::default()
+    };
+    let mut rng = ChaCha20Rng::from_seed([7; 32]);
+    let params = generate_parameters(&config, &mut rng).unwrap();
+    params::save_verifying_key(golden("vk.bin"), &config, &params.vk).unwrap();
+    let bundle = ProofBundle::create(&params, &config, b"verifier".to_vec(), &mut rng).unwrap();
+    let mut encoded = vec![];
+    bundle.write(&mut encoded).unwrap();
+    fs::write(golden("proof.bin"), encoded).unwrap();
+    fs::write(golden("proof.json"), bundle.to_json() + "\n").unwrap();
+}
+
+#[test]
+fn verifies_golden_bundles() {
+    #[cfg(feature = "prover")]
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        update_golden();
+    }
+
+    let (config, vk) = params::load_verifying_key(golden("vk.bin")).unwrap();
+    assert_eq!((config.input_size, config.output_size), (8, 2));
+    let bundle = ProofBundle::read(&fs::read(golden("proof.bin")).unwrap()[..]).unwrap();
+    let json = ProofBundle::from_json(&fs::read_to_string(golden("proof.json")).unwrap()).unwrap();
+    assert!(bundle == json);
+    assert!(bundle.is_for(&vk));
+
+    let pvk = groth16::prepare_verifying_key(&vk);
+    assert!(bundle.verify(&pvk, config.output_size).is_ok());
+    let mut tampered = bundle.clone();
+    tampered.hash[1] ^= 1;
+    assert!(matches!(
+        tampered.verify(&pvk, config.output_size),
+        Err(Error::Verification(_))
+    ));
+
+    let verifier = BatchVerifier::new(&vk);
+    assert!(verifier
+        .verify_bundles(&[bundle.clone(), json], config.output_size, &mut OsRng)
+        .is_ok());
+    assert!(matches!(
+        verifier.verify_bundles(&[bundle, tampered], config.output_size, &mut OsRng),
+        Err(Error::InvalidProofs(invalid)) if invalid == [1]
+    ));
+}
+
+fn too_long<T>(result: Result<T, Error>) -> bool {
+    matches!(
+        result,
+        Err(Error::InvalidLength {
+            min: 2,
+            max: 2,
+            got: 3,
+            ..
+        })
+    )
+}
+
+#[test]
+fn zero_padded_hashes_are_rejected() {
+    let (config, vk) = params::load_verifying_key(golden("vk.bin")).unwrap();
+    let bundle = ProofBundle::read(&fs::read(golden("proof.bin")).unwrap()[..]).unwrap();
+    let pvk = groth16::prepare_verifying_key(&vk);
+
+    // Trailing zero bits don't change the packed inputs, so only the length
+    // tells this hash apart from the one the circuit computed.
+    let mut padded = bundle.clone();
+    padded.hash.push(0);
+    assert!(too_long(padded.public_inputs(config.output_size)));
+    assert!(too_long(padded.verify(&pvk, config.output_size)));
+    assert!(too_long(BatchVerifier::new(&vk).verify_bundles(
+        &[bundle, padded],
+        config.output_size,
+        &mut OsRng
+    )));
+}
+
+/// Runs `cargo` on this crate with only the `verifier` feature.
+#[cfg(feature = "prover")]
+fn cargo_verifier_only(args: &[&str]) -> std::process::Output {
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    std::process::Command::new(env!("CARGO"))
+        .args(args)
+        .args(["--no-default-features", "--features", "verifier"])
+        .current_dir(manifest_dir)
+        // A target directory of its own, so the build doesn't wait for the
+        // lock of the one running this test, or undo its feature selection.
+        .env(
+            "CARGO_TARGET_DIR",
+            PathBuf::from(manifest_dir).join("target/verifier-only"),
+        )
+        .output()
+        .unwrap()
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn verifier_only_build() {
+    let tree = cargo_verifier_only(&["tree", "--edges", "normal", "--prefix", "none"]);
+    assert!(
+        tree.status.success(),
+        "{}",
+        String::from_utf8_lossy(&tree.stderr)
+    );
+    let tree = String::from_utf8(tree.stdout).unwrap();
+    let crates = tree
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .collect::<Vec<_>>();
+    assert!(crates.contains(&"bellman"));
+    for name in &[
+        "rand",
+        "simple-logging",
+        "log",
+        "clap",
+        "tiny-keccak",
+        "futures-cpupool",
+        "crossbeam",
+        "num_cpus",
+    ] {
+        assert!(
+            !crates.contains(name),
+            "verifier-only build depends on {}",
+            name
+        );
+    }
+
+    let test = cargo_verifier_only(&["test", "--test", "verifier"]);
+    assert!(
+        test.status.success(),
+        "{}{}",
+        String::from_utf8_lossy(&test.stdout),
+        String::from_utf8_lossy(&test.stderr)
+    );
+    assert!(String::from_utf8_lossy(&test.stdout).contains("test verifies_golden_bundles ... ok"));
+}